@@ -56,6 +56,9 @@ export default function Contract() {
             <p className="font-normal mt-0.5 text-sm text-gray-600">
               {`Address: ${contractState.pda.toString()}`}
             </p>
+            <p className="font-normal mt-0.5 text-sm text-gray-600">
+              {`Vault: ${contractState.vault.toString()}`}
+            </p>
             <p className="font-normal mt-0.5 text-sm text-gray-600">
               {`Mnemonic: ${contractState.mnemonic}`}
             </p>
@@ -8,36 +8,29 @@ import {
   Transaction,
   LAMPORTS_PER_SOL,
 } from "@solana/web3.js";
-import { MULTI_SIG_WALLET, RESERVED_PUBLIC_KEY } from "../constants/program";
+import BN from "bn.js";
+import { MULTI_SIG_WALLET, OWNER_REGISTRY, RECEIPT, RESERVED_PUBLIC_KEY, VAULT } from "../constants/program";
 import InstructionVariant from "../utils/instructionsVariants";
 import TransactionVariant from "../utils/transactionVariant";
 import { ConnectionManagerContext } from "./connectionManager";
 import encodeInstructionData, {InstructionData} from "../utils/encodeInstructionData";
+import decodeWalletState from "../utils/decodeWalletState";
 
 interface Props {
   connectionManager: typeof ConnectionManagerContext;
 }
 
 interface TransactionDetails {
+  variant: TransactionVariant;
   toAddress: string;
   signerPublicKeyStrings: string[];
   opponentPublicKeyStrings: string[];
 }
 
-type OptionalAccountMeta = Omit<Partial<AccountMeta>, 'pubkey'> & {
-  pubkey: PublicKey,
-}
-
-interface AccountMetas {
-  signer: OptionalAccountMeta,
-  base: OptionalAccountMeta,
-  pda: OptionalAccountMeta,
-  to?: OptionalAccountMeta,
-}
-
 interface Contract {
   programId: PublicKey;
   pda: PublicKey;
+  vault: PublicKey;
   baseKeypair: Keypair;
   mnemonic: string;
   balance: number;
@@ -65,6 +58,7 @@ export const ContractManagerContext = createContext<ContractManager>({
   contractState: {
     programId: RESERVED_PUBLIC_KEY,
     pda: RESERVED_PUBLIC_KEY,
+    vault: RESERVED_PUBLIC_KEY,
     baseKeypair: new Keypair(),
     mnemonic: "",
     balance: 0,
@@ -111,6 +105,7 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   const [contract, setContract] = useState<Contract>({
     programId: RESERVED_PUBLIC_KEY,
     pda: RESERVED_PUBLIC_KEY,
+    vault: RESERVED_PUBLIC_KEY,
     baseKeypair: new Keypair(),
     mnemonic: "",
     balance: 0,
@@ -123,38 +118,12 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   const createAndConfirmTransaction = async (
     programId: PublicKey,
     instructionData: InstructionData,
-    accountMetas: AccountMetas,
+    keys: AccountMeta[],
     signers: Keypair[],
   ) => {
     const data = encodeInstructionData(instructionData);
     const transaction = new Transaction().add({
-      keys: [
-        {
-          pubkey: accountMetas.signer.pubkey,
-          isSigner: accountMetas.signer?.isSigner ?? true,
-          isWritable: accountMetas.signer?.isWritable ?? false
-        },
-        {
-          pubkey: accountMetas.base.pubkey,
-          isSigner: accountMetas.base?.isSigner ?? false,
-          isWritable: accountMetas.base?.isWritable ?? false
-        },
-        {
-          pubkey: accountMetas.pda.pubkey,
-          isSigner: accountMetas.pda?.isSigner ?? false,
-          isWritable: accountMetas.pda?.isWritable ?? true
-        },
-        {
-          pubkey: accountMetas.to?.pubkey ?? RESERVED_PUBLIC_KEY,
-          isSigner: accountMetas.to?.isSigner ?? false,
-          isWritable: accountMetas.to?.isWritable ?? false
-        },
-        {
-          pubkey: SystemProgram.programId,
-          isSigner: false,
-          isWritable: false,
-        }
-      ],
+      keys,
       programId: programId,
       data,
     });
@@ -166,6 +135,46 @@ export function ContractManagerContextProvider({ children, connectionManager }:
     }));
   };
 
+  const vaultAddress = (programId: PublicKey, pda: PublicKey, vaultIndex: number) => PublicKey.findProgramAddressSync(
+    [Buffer.from(VAULT, 'utf-8'), pda.toBuffer(), Buffer.from([vaultIndex])],
+    programId,
+  )[0];
+
+  const receiptAddress = (transactionId: BN) => PublicKey.findProgramAddressSync(
+    [Buffer.from(RECEIPT, 'utf-8'), contract.pda.toBuffer(), transactionId.toArrayLike(Buffer, 'le', 8)],
+    contract.programId,
+  )[0];
+
+  const registryMetas = (programId: PublicKey, owners: PublicKey[]): AccountMeta[] => owners.map((owner) => ({
+    pubkey: PublicKey.findProgramAddressSync([Buffer.from(OWNER_REGISTRY, 'utf-8'), owner.toBuffer()], programId)[0],
+    isSigner: false,
+    isWritable: true,
+  }));
+
+  const walletMetas = (signer: Keypair): AccountMeta[] => [
+    { pubkey: signer.publicKey, isSigner: true, isWritable: true },
+    { pubkey: contract.baseKeypair.publicKey, isSigner: false, isWritable: false },
+    { pubkey: contract.pda, isSigner: false, isWritable: true },
+  ];
+
+  const fetchWalletState = async () => {
+    const account = await connection.getAccountInfo(contract.pda);
+    if (account === null) {
+      throw new Error('Wallet account not found!');
+    }
+
+    return decodeWalletState(account.data);
+  };
+
+  const closingMetas = async (): Promise<AccountMeta[]> => {
+    const { transaction } = await fetchWalletState();
+
+    return [
+      { pubkey: receiptAddress(transaction.id), isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ];
+  };
+
   const createMultiSigWallet = async (signer: Keypair, programIdPublicKeyString: string) => {
     const programId = new PublicKey(programIdPublicKeyString);
 
@@ -178,15 +187,20 @@ export function ContractManagerContextProvider({ children, connectionManager }:
       programId,
     );
 
+    const owners = contract.ownerPublicKeyStrings.map((owner) => new PublicKey(owner));
+
     await createAndConfirmTransaction(programId, {
       id: InstructionVariant.CreateWallet,
-      owners: contract.ownerPublicKeyStrings.map((owner) => new PublicKey(owner)),
+      owners,
       threshold: contract.threshold,
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: baseKeypair.publicKey, isSigner: true },
-      pda: { pubkey: pda },
-    }, [signer, baseKeypair]);
+    }, [
+      { pubkey: signer.publicKey, isSigner: true, isWritable: true },
+      { pubkey: baseKeypair.publicKey, isSigner: true, isWritable: false },
+      { pubkey: pda, isSigner: false, isWritable: true },
+      { pubkey: RESERVED_PUBLIC_KEY, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ...registryMetas(programId, owners),
+    ], [signer, baseKeypair]);
 
     setContract((prevState) => ({
       ...prevState,
@@ -194,6 +208,7 @@ export function ContractManagerContextProvider({ children, connectionManager }:
       baseKeypair,
       mnemonic,
       pda,
+      vault: vaultAddress(programId, pda, 0),
     }));
   };
 
@@ -202,17 +217,16 @@ export function ContractManagerContextProvider({ children, connectionManager }:
       id: InstructionVariant.CreateTransaction,
       variant: TransactionVariant.SetOwners,
       owners: ownerPublicKeyStrings.map((ownerPublicKeyString) => new PublicKey(ownerPublicKeyString)),
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: contract.baseKeypair.publicKey },
-      pda: { pubkey: contract.pda },
-      to: { pubkey: contract.pda },
-    }, [signer]);
+    }, [
+      ...walletMetas(signer),
+      { pubkey: contract.pda, isSigner: false, isWritable: false },
+    ], [signer]);
 
     setContract((prevState) => ({
       ...prevState,
       ownerPublicKeyStrings,
       transactionDetails: contract.mnemonic === '' ? null : {
+        variant: TransactionVariant.SetOwners,
         toAddress: contract.pda.toString(),
         signerPublicKeyStrings: [signer.publicKey.toString()],
         opponentPublicKeyStrings: []
@@ -223,19 +237,18 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   const setThreshold = async (signer: Keypair, threshold: number) => {
     contract.mnemonic !== '' && await createAndConfirmTransaction(contract.programId, {
       id: InstructionVariant.CreateTransaction,
-      variant: TransactionVariant.SetOwners,
+      variant: TransactionVariant.SetThreshold,
       threshold,
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: contract.baseKeypair.publicKey },
-      pda: { pubkey: contract.pda },
-      to: { pubkey: contract.pda },
-    }, [signer]);
+    }, [
+      ...walletMetas(signer),
+      { pubkey: contract.pda, isSigner: false, isWritable: false },
+    ], [signer]);
 
     setContract((prevState) => ({
       ...prevState,
       threshold,
       transactionDetails: contract.mnemonic === '' ? null : {
+        variant: TransactionVariant.SetThreshold,
         toAddress: contract.pda.toString(),
         signerPublicKeyStrings: [signer.publicKey.toString()],
         opponentPublicKeyStrings: []
@@ -248,17 +261,17 @@ export function ContractManagerContextProvider({ children, connectionManager }:
       id: InstructionVariant.CreateTransaction,
       variant: TransactionVariant.Send,
       amount,
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: contract.baseKeypair.publicKey },
-      pda: { pubkey: contract.pda },
-      to: { pubkey: to }
-    }, [signer]);
+    }, [
+      ...walletMetas(signer),
+      { pubkey: to, isSigner: false, isWritable: false },
+      { pubkey: contract.vault, isSigner: false, isWritable: false },
+    ], [signer]);
 
     setContract((prevState) => ({
       ...prevState,
       balance: prevState.balance - amount,
       transactionDetails: {
+        variant: TransactionVariant.Send,
         toAddress: to.toString(),
         signerPublicKeyStrings: [signer.publicKey.toString()],
         opponentPublicKeyStrings: []
@@ -267,13 +280,16 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   };
 
   const confirmTransaction = async (signer: Keypair) => {
+    const { transaction } = await fetchWalletState();
+
     await createAndConfirmTransaction(contract.programId, {
       id: InstructionVariant.ConfirmTransaction,
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: contract.baseKeypair.publicKey },
-      pda: { pubkey: contract.pda },
-    }, [signer]);
+      transactionId: transaction.id,
+      transactionHash: Uint8Array.from(transaction.hash),
+    }, [
+      ...walletMetas(signer),
+      ...await closingMetas(),
+    ], [signer]);
 
     setContract((prevState) => ({
       ...prevState,
@@ -290,11 +306,10 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   const rejectTransaction = async (signer: Keypair) => {
     await createAndConfirmTransaction(contract.programId, {
       id: InstructionVariant.RejectTransaction,
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: contract.baseKeypair.publicKey },
-      pda: { pubkey: contract.pda },
-    }, [signer]);
+    }, [
+      ...walletMetas(signer),
+      ...await closingMetas(),
+    ], [signer]);
 
     setContract((prevState) => ({
       ...prevState,
@@ -309,14 +324,31 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   };
   
   const executeTransaction = async (signer: Keypair) => {
+    const { variant, toAddress } = contract.transactionDetails!;
+    const walletState = await fetchWalletState();
+    const { transaction } = walletState;
+
+    // Send pays out of the vault, SetOwners updates the registries of the
+    // owners that join or leave the wallet.
+    const variantMetas: AccountMeta[] = variant === TransactionVariant.Send
+      ? [{ pubkey: contract.vault, isSigner: false, isWritable: true }]
+      : variant === TransactionVariant.SetOwners
+        ? registryMetas(contract.programId, walletState.owners.concat(
+          transaction.owners.filter((owner) => !walletState.owners.some((current) => current.equals(owner)))
+        ))
+        : [];
+
     await createAndConfirmTransaction(contract.programId, {
       id: InstructionVariant.ExecuteTransaction,
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: contract.baseKeypair.publicKey },
-      pda: { pubkey: contract.pda },
-      to: { pubkey: new PublicKey(contract.transactionDetails!.toAddress) }
-    }, [signer]);
+      transactionId: transaction.id,
+      transactionHash: Uint8Array.from(transaction.hash),
+    }, [
+      ...walletMetas(signer),
+      { pubkey: new PublicKey(toAddress), isSigner: false, isWritable: true },
+      { pubkey: receiptAddress(transaction.id), isSigner: false, isWritable: true },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ...variantMetas,
+    ], [signer]);
 
     setContract((prevState) => ({
       ...prevState,
@@ -327,11 +359,10 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   const cancelTransaction = async (signer: Keypair) => {
     await createAndConfirmTransaction(contract.programId, {
       id: InstructionVariant.CancelTransaction,
-    }, {
-      signer: { pubkey: signer.publicKey },
-      base: { pubkey: contract.baseKeypair.publicKey },
-      pda: { pubkey: contract.pda },
-    }, [signer]);
+    }, [
+      ...walletMetas(signer),
+      ...await closingMetas(),
+    ], [signer]);
 
     setContract((prevState) => ({
       ...prevState,
@@ -340,7 +371,7 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   };
 
   const refreshBalance = async () => {
-    const balance = await connection.getBalance(contract.pda);
+    const balance = await connection.getBalance(contract.vault);
 
     setContract((prevState) => ({
       ...prevState,
@@ -349,7 +380,7 @@ export function ContractManagerContextProvider({ children, connectionManager }:
   };
 
   const requestAirdrop = async () => {
-    const tx = await connection.requestAirdrop(contract.pda, LAMPORTS_PER_SOL);
+    const tx = await connection.requestAirdrop(contract.vault, LAMPORTS_PER_SOL);
     setContract((prevState) => ({ ...prevState, tx }));
   };
 
@@ -362,7 +393,7 @@ export function ContractManagerContextProvider({ children, connectionManager }:
           return;
         }
 
-        const balance = await connection.getBalance(contract.pda);
+        const balance = await connection.getBalance(contract.vault);
         setContract((prevState) => ({
           ...prevState,
           balance,
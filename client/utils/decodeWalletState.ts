@@ -0,0 +1,104 @@
+import BN from "bn.js";
+import { PublicKey } from "@solana/web3.js";
+import {
+  array,
+  bool,
+  i64,
+  option,
+  publicKey,
+  str,
+  struct,
+  u64,
+  u8,
+  vec,
+} from "@project-serum/borsh";
+import TransactionVariant from "./transactionVariant";
+
+export enum TransactionStatus {
+  Empty,
+  Active,
+  Approved,
+  Executed,
+  Rejected,
+  Cancelled,
+  Expired,
+}
+
+export interface TransactionState {
+  id: BN,
+  status: TransactionStatus,
+  variant: TransactionVariant,
+  proposer: PublicKey,
+  signers: PublicKey[],
+  opponents: PublicKey[],
+  toAddress: PublicKey,
+  amount: BN,
+  owners: PublicKey[],
+  threshold: BN,
+  vaultIndex: number,
+  hash: number[],
+}
+
+export interface WalletState {
+  isInitialized: boolean,
+  owners: PublicKey[],
+  threshold: BN,
+  seed: PublicKey,
+  transactionCount: BN,
+  transaction: TransactionState,
+}
+
+const transactionLayout = (property: string) => struct([
+  u64("id"),
+  u8("status"),
+  u8("variant"),
+  publicKey("proposer"),
+  vec(publicKey(), "signers"),
+  vec(publicKey(), "opponents"),
+  publicKey("toAddress"),
+  u64("amount"),
+  vec(publicKey(), "owners"),
+  u64("threshold"),
+  u8("vaultIndex"),
+  vec(publicKey(), "guardians"),
+  u64("guardianThreshold"),
+  i64("inactivityPeriod"),
+  i64("recoveryDelay"),
+  i64("period"),
+  bool("allowlistEnabled"),
+  vec(publicKey(), "allowlist"),
+  u64("allowlistBypassThreshold"),
+  u64("vetoThreshold"),
+  i64("expiresAt"),
+  vec(publicKey(), "cancellers"),
+  bool("ownersOnlyExecution"),
+  publicKey("owner"),
+  publicKey("newOwner"),
+  vec(publicKey(), "accounts"),
+  u8("authorityType"),
+  u8("recipientRentPayer"),
+  array(u8(), 32, "batchHash"),
+  bool("batchChunked"),
+  u64("batchProgress"),
+  i64("startAt"),
+  i64("endAt"),
+  i64("cliffAt"),
+  str("description"),
+  option(array(u8(), 32), "reference"),
+  array(u8(), 32, "hash"),
+], property);
+
+// Only the leading fields of the wallet account are decoded, the rest of the
+// account is not needed by the client.
+const WALLET_LAYOUT = struct([
+  bool("isInitialized"),
+  vec(publicKey(), "owners"),
+  u64("threshold"),
+  publicKey("seed"),
+  u64("transactionCount"),
+  transactionLayout("transaction"),
+]);
+
+export default function decodeWalletState(data: Buffer): WalletState {
+  return WALLET_LAYOUT.decode(data) as WalletState;
+}
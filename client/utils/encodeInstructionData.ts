@@ -1,45 +1,295 @@
 import BN from "bn.js";
 import { PublicKey } from "@solana/web3.js";
-import { Layout, publicKey, struct, u64, u8, vec } from "@project-serum/borsh";
+import {
+  Layout,
+  array,
+  bool,
+  i64,
+  option,
+  publicKey,
+  str,
+  struct,
+  u64,
+  u8,
+  vec,
+  vecU8,
+} from "@project-serum/borsh";
 import InstructionVariant from "./instructionsVariants";
 import TransactionVariant from "./transactionVariant";
 
-export interface InstructionData {
-  id: InstructionVariant,
+export enum RentPayer {
+  None,
+  Vault,
+  Executor,
+}
+
+export enum BatchActionKind {
+  Send,
+  TransferTokens,
+  SetThreshold,
+  SetVetoThreshold,
+  Invoke,
+}
+
+export interface BatchAccountMeta {
+  pubkey: PublicKey,
+  isSigner: boolean,
+  isWritable: boolean,
+}
+
+export interface BatchAction {
+  kind: BatchActionKind,
+  toAddress: PublicKey,
+  amount: number,
+  vaultIndex: number,
+  mint: PublicKey,
+  accounts: BatchAccountMeta[],
+  data: Buffer,
+}
+
+export interface CreateWalletData {
+  id: InstructionVariant.CreateWallet,
+  owners: PublicKey[],
+  threshold: number,
+  guardians?: PublicKey[],
+  guardianThreshold?: number,
+  inactivityPeriod?: number,
+  recoveryDelay?: number,
+  ownersOnlyExecution?: boolean,
+}
+
+export interface CreateTransactionData {
+  id: InstructionVariant.CreateTransaction,
+  variant: TransactionVariant,
   amount?: number,
   owners?: PublicKey[],
   threshold?: number,
-  variant?: TransactionVariant,
+  vaultIndex?: number,
+  guardians?: PublicKey[],
+  guardianThreshold?: number,
+  inactivityPeriod?: number,
+  recoveryDelay?: number,
+  period?: number,
+  allowlistEnabled?: boolean,
+  allowlist?: PublicKey[],
+  allowlistBypassThreshold?: number,
+  vetoThreshold?: number,
+  expiresAt?: number,
+  ownersOnlyExecution?: boolean,
+  owner?: PublicKey,
+  newOwner?: PublicKey,
+  accounts?: PublicKey[],
+  authorityType?: number,
+  recipientRentPayer?: RentPayer,
+  actions?: BatchAction[],
+  batchChunked?: boolean,
+  startAt?: number,
+  endAt?: number,
+  cliffAt?: number,
+  description?: string,
+  reference?: Uint8Array | null,
+}
+
+export interface TransactionReferenceData {
+  id: InstructionVariant.ConfirmTransaction | InstructionVariant.ExecuteTransaction,
+  transactionId: BN,
+  transactionHash: Uint8Array,
 }
 
-const LAYOUT: Layout<Omit<Required<InstructionData>, 'threshold' | 'amount'> & {
-  threshold: BN,
-  amount: BN,
-}> = struct([
-  u8("id"),
+export interface InitiateRecoveryData {
+  id: InstructionVariant.InitiateRecovery,
+  owners: PublicKey[],
+  threshold: number,
+}
+
+export interface SpendWithinLimitData {
+  id: InstructionVariant.SpendWithinLimit,
+  amount: number,
+  vaultIndex: number,
+}
+
+export interface AppendBatchActionsData {
+  id: InstructionVariant.AppendBatchActions,
+  transactionId: BN,
+  actions: BatchAction[],
+}
+
+export interface EmptyData {
+  id: InstructionVariant.RejectTransaction
+    | InstructionVariant.CancelTransaction
+    | InstructionVariant.SweepLegacyWallet
+    | InstructionVariant.ApproveRecovery
+    | InstructionVariant.ExecuteRecovery
+    | InstructionVariant.CancelRecovery
+    | InstructionVariant.Pause
+    | InstructionVariant.ClaimScheduledPayment
+    | InstructionVariant.WithdrawVested,
+}
+
+export type InstructionData =
+  | CreateWalletData
+  | CreateTransactionData
+  | TransactionReferenceData
+  | InitiateRecoveryData
+  | SpendWithinLimitData
+  | AppendBatchActionsData
+  | EmptyData;
+
+const BATCH_ACTION_LAYOUT = struct([
+  u8("kind"),
+  publicKey("toAddress"),
+  u64("amount"),
+  u8("vaultIndex"),
+  publicKey("mint"),
+  vec(struct([publicKey("pubkey"), bool("isSigner"), bool("isWritable")]), "accounts"),
+  vecU8("data"),
+]);
+
+const CREATE_WALLET_LAYOUT = struct([
+  vec(publicKey(), "owners"),
+  u64("threshold"),
+  vec(publicKey(), "guardians"),
+  u64("guardianThreshold"),
+  i64("inactivityPeriod"),
+  i64("recoveryDelay"),
+  bool("ownersOnlyExecution"),
+]);
+
+const CREATE_TRANSACTION_LAYOUT = struct([
   u8("variant"),
   u64("amount"),
   vec(publicKey(), "owners"),
   u64("threshold"),
+  u8("vaultIndex"),
+  vec(publicKey(), "guardians"),
+  u64("guardianThreshold"),
+  i64("inactivityPeriod"),
+  i64("recoveryDelay"),
+  i64("period"),
+  bool("allowlistEnabled"),
+  vec(publicKey(), "allowlist"),
+  u64("allowlistBypassThreshold"),
+  u64("vetoThreshold"),
+  i64("expiresAt"),
+  bool("ownersOnlyExecution"),
+  publicKey("owner"),
+  publicKey("newOwner"),
+  vec(publicKey(), "accounts"),
+  u8("authorityType"),
+  u8("recipientRentPayer"),
+  vec(BATCH_ACTION_LAYOUT, "actions"),
+  bool("batchChunked"),
+  i64("startAt"),
+  i64("endAt"),
+  i64("cliffAt"),
+  str("description"),
+  option(array(u8(), 32), "reference"),
+]);
+
+const TRANSACTION_REFERENCE_LAYOUT = struct([
+  u64("transactionId"),
+  array(u8(), 32, "transactionHash"),
+]);
+
+const INITIATE_RECOVERY_LAYOUT = struct([
+  vec(publicKey(), "owners"),
+  u64("threshold"),
 ]);
 
-export default function encodeInstructionData({
-  id,
-  owners,
-  threshold,
-  amount,
-  variant,
-}: InstructionData)  {
-  let data = Buffer.alloc(1000);
-  LAYOUT.encode(
-    {
-      id,
-      variant: variant ?? 0,
-      owners: owners ?? [],
-      threshold: new BN(threshold ?? 0),
-      amount: new BN(amount ?? 0),
-    },
-    data
-  );
-  return data.subarray(0, LAYOUT.getSpan(data));
-}
\ No newline at end of file
+const SPEND_WITHIN_LIMIT_LAYOUT = struct([
+  u64("amount"),
+  u8("vaultIndex"),
+]);
+
+const APPEND_BATCH_ACTIONS_LAYOUT = struct([
+  u64("transactionId"),
+  vec(BATCH_ACTION_LAYOUT, "actions"),
+]);
+
+const encodeBatchAction = (action: BatchAction) => ({
+  ...action,
+  amount: new BN(action.amount),
+});
+
+function encodePayload(instruction: InstructionData): [Layout<any>, any] | null {
+  switch (instruction.id) {
+    case InstructionVariant.CreateWallet:
+      return [CREATE_WALLET_LAYOUT, {
+        owners: instruction.owners,
+        threshold: new BN(instruction.threshold),
+        guardians: instruction.guardians ?? [],
+        guardianThreshold: new BN(instruction.guardianThreshold ?? 0),
+        inactivityPeriod: new BN(instruction.inactivityPeriod ?? 0),
+        recoveryDelay: new BN(instruction.recoveryDelay ?? 0),
+        ownersOnlyExecution: instruction.ownersOnlyExecution ?? false,
+      }];
+    case InstructionVariant.CreateTransaction:
+      return [CREATE_TRANSACTION_LAYOUT, {
+        variant: instruction.variant,
+        amount: new BN(instruction.amount ?? 0),
+        owners: instruction.owners ?? [],
+        threshold: new BN(instruction.threshold ?? 0),
+        vaultIndex: instruction.vaultIndex ?? 0,
+        guardians: instruction.guardians ?? [],
+        guardianThreshold: new BN(instruction.guardianThreshold ?? 0),
+        inactivityPeriod: new BN(instruction.inactivityPeriod ?? 0),
+        recoveryDelay: new BN(instruction.recoveryDelay ?? 0),
+        period: new BN(instruction.period ?? 0),
+        allowlistEnabled: instruction.allowlistEnabled ?? false,
+        allowlist: instruction.allowlist ?? [],
+        allowlistBypassThreshold: new BN(instruction.allowlistBypassThreshold ?? 0),
+        vetoThreshold: new BN(instruction.vetoThreshold ?? 0),
+        expiresAt: new BN(instruction.expiresAt ?? 0),
+        ownersOnlyExecution: instruction.ownersOnlyExecution ?? false,
+        owner: instruction.owner ?? PublicKey.default,
+        newOwner: instruction.newOwner ?? PublicKey.default,
+        accounts: instruction.accounts ?? [],
+        authorityType: instruction.authorityType ?? 0,
+        recipientRentPayer: instruction.recipientRentPayer ?? RentPayer.None,
+        actions: (instruction.actions ?? []).map(encodeBatchAction),
+        batchChunked: instruction.batchChunked ?? false,
+        startAt: new BN(instruction.startAt ?? 0),
+        endAt: new BN(instruction.endAt ?? 0),
+        cliffAt: new BN(instruction.cliffAt ?? 0),
+        description: instruction.description ?? "",
+        reference: instruction.reference ? Array.from(instruction.reference) : null,
+      }];
+    case InstructionVariant.ConfirmTransaction:
+    case InstructionVariant.ExecuteTransaction:
+      return [TRANSACTION_REFERENCE_LAYOUT, {
+        transactionId: instruction.transactionId,
+        transactionHash: Array.from(instruction.transactionHash),
+      }];
+    case InstructionVariant.InitiateRecovery:
+      return [INITIATE_RECOVERY_LAYOUT, {
+        owners: instruction.owners,
+        threshold: new BN(instruction.threshold),
+      }];
+    case InstructionVariant.SpendWithinLimit:
+      return [SPEND_WITHIN_LIMIT_LAYOUT, {
+        amount: new BN(instruction.amount),
+        vaultIndex: instruction.vaultIndex,
+      }];
+    case InstructionVariant.AppendBatchActions:
+      return [APPEND_BATCH_ACTIONS_LAYOUT, {
+        transactionId: instruction.transactionId,
+        actions: instruction.actions.map(encodeBatchAction),
+      }];
+    default:
+      return null;
+  }
+}
+
+export default function encodeInstructionData(instruction: InstructionData) {
+  let data = Buffer.alloc(1232);
+  data.writeUInt8(instruction.id, 0);
+
+  const payload = encodePayload(instruction);
+  if (payload === null) {
+    return data.subarray(0, 1);
+  }
+
+  const [layout, value] = payload;
+  layout.encode(value, data, 1);
+  return data.subarray(0, 1 + layout.getSpan(data, 1));
+}
@@ -5,6 +5,16 @@ enum InstructionVariant {
   RejectTransaction,
   ExecuteTransaction,
   CancelTransaction,
+  SweepLegacyWallet,
+  InitiateRecovery,
+  ApproveRecovery,
+  ExecuteRecovery,
+  CancelRecovery,
+  Pause,
+  SpendWithinLimit,
+  AppendBatchActions,
+  ClaimScheduledPayment,
+  WithdrawVested,
 }
 
 export default InstructionVariant;
@@ -2,6 +2,36 @@ enum TransactionVariant {
   SetOwners,
   SetThreshold,
   Send,
+  SetGuardians,
+  Unpause,
+  SetSpendingLimit,
+  SetAllowlist,
+  SetVetoThreshold,
+  SetExecutionPolicy,
+  AddOwner,
+  RemoveOwner,
+  SwapOwner,
+  Reconfigure,
+  UpgradeProgram,
+  SetUpgradeAuthority,
+  DelegateStake,
+  DeactivateStake,
+  WithdrawStake,
+  SplitStake,
+  MergeStake,
+  MintTokens,
+  BurnTokens,
+  SetTokenAuthority,
+  FreezeTokenAccount,
+  ThawTokenAccount,
+  TransferTokens,
+  WrapSol,
+  UnwrapSol,
+  Batch,
+  CreateSchedule,
+  CancelSchedule,
+  CreateVesting,
+  CancelVesting,
 }
 
 export default TransactionVariant;
@@ -9,10 +9,10 @@ pub enum MultiSigWalletError {
     #[error("PDA does not equal PDA passed in")]
     InvalidPDA,
 
-    #[error("Threshold greater than 3 or less than 2")]
+    #[error("Threshold out of range for the number of owners")]
     InvalidThreshold,
 
-    #[error("Length of the owners is greater than 3 or less than 2")]
+    #[error("Length of the owners is greater than the maximum or less than the threshold")]
     InvalidOwnersLength,
 
     #[error("Initializer not an owner in the wallet")]
@@ -29,6 +29,54 @@ pub enum MultiSigWalletError {
 
     #[error("Not enough approvals")]
     NotEnoughApprovals,
+
+    #[error("Vault does not equal vault passed in")]
+    InvalidVault,
+
+    #[error("Guardian set or recovery parameters are invalid")]
+    InvalidGuardians,
+
+    #[error("Initializer not a guardian of the wallet")]
+    InvalidGuardian,
+
+    #[error("Recovery not available yet")]
+    RecoveryNotAvailable,
+
+    #[error("Wallet is paused")]
+    WalletPaused,
+
+    #[error("Spending limit exceeded")]
+    SpendingLimitExceeded,
+
+    #[error("Destination not in the allowlist")]
+    DestinationNotAllowed,
+
+    #[error("Allowlist or bypass threshold is invalid")]
+    InvalidAllowlist,
+
+    #[error("Owner already in the wallet")]
+    OwnerAlreadyExists,
+
+    #[error("Owner not found in the wallet")]
+    OwnerNotFound,
+
+    #[error("Owner appears more than once")]
+    DuplicateOwner,
+
+    #[error("Key cannot be an owner of the wallet")]
+    ForbiddenOwner,
+
+    #[error("Transaction does not match the expected id or hash")]
+    TransactionMismatch,
+
+    #[error("Receipt does not equal receipt passed in")]
+    InvalidReceipt,
+
+    #[error("Accounts passed in do not match the transaction")]
+    InvalidTransactionAccounts,
+
+    #[error("Invalid batch")]
+    InvalidBatch,
 }
 
 impl From<MultiSigWalletError> for ProgramError {
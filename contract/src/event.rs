@@ -0,0 +1,189 @@
+use borsh::{
+    BorshSerialize
+};
+use solana_program::{
+    entrypoint::ProgramResult,
+    hash::hashv,
+    pubkey::Pubkey,
+    msg
+};
+use crate::state::{
+    MultiSigWalletState,
+    TransactionStatus,
+    TransactionVariant
+};
+
+#[derive(BorshSerialize, Debug)]
+pub enum MultiSigWalletEvent {
+    WalletCreated {
+        owners: Vec<Pubkey>,
+        threshold: u64,
+        guardians: Vec<Pubkey>,
+        guardian_threshold: u64,
+        inactivity_period: i64,
+        recovery_delay: i64,
+        owners_only_execution: bool
+    },
+    TransactionCreated {
+        id: u64,
+        variant: TransactionVariant,
+        proposer: Pubkey,
+        hash: [u8; 32]
+    },
+    TransactionConfirmed {
+        id: u64,
+        owner: Pubkey
+    },
+    TransactionRejected {
+        id: u64,
+        owner: Pubkey
+    },
+    CancellationRequested {
+        id: u64,
+        owner: Pubkey
+    },
+    TransactionClosed {
+        id: u64,
+        status: TransactionStatus
+    },
+    OwnersSet {
+        owners: Vec<Pubkey>
+    },
+    ThresholdSet {
+        threshold: u64
+    },
+    Sent {
+        vault_index: u8,
+        to_address: Pubkey,
+        amount: u64
+    },
+    GuardiansSet {
+        guardians: Vec<Pubkey>,
+        guardian_threshold: u64,
+        inactivity_period: i64,
+        recovery_delay: i64
+    },
+    Unpaused,
+    SpendingLimitSet {
+        amount: u64,
+        period: i64,
+        period_start: i64
+    },
+    AllowlistSet {
+        allowlist_enabled: bool,
+        allowlist: Vec<Pubkey>,
+        allowlist_bypass_threshold: u64
+    },
+    VetoThresholdSet {
+        veto_threshold: u64
+    },
+    ExecutionPolicySet {
+        owners_only_execution: bool
+    },
+    OwnerAdded {
+        owner: Pubkey
+    },
+    OwnerRemoved {
+        owner: Pubkey
+    },
+    OwnerSwapped {
+        old_owner: Pubkey,
+        new_owner: Pubkey
+    },
+    Reconfigured {
+        owners: Vec<Pubkey>,
+        threshold: u64
+    },
+    RecoveryInitiated {
+        guardian: Pubkey,
+        owners: Vec<Pubkey>,
+        threshold: u64
+    },
+    RecoveryApproved {
+        guardian: Pubkey
+    },
+    RecoveryExecuted {
+        owners: Vec<Pubkey>,
+        threshold: u64
+    },
+    RecoveryCancelled,
+    Paused {
+        paused_by: Pubkey
+    },
+    StakeUpdated {
+        stake_account: Pubkey,
+        lamports: u64,
+        voter: Pubkey,
+        delegated_stake: u64,
+        activation_epoch: u64,
+        deactivation_epoch: u64
+    },
+    BatchActionsAppended {
+        id: u64,
+        actions: u64,
+        hash: [u8; 32]
+    },
+    BatchProgress {
+        id: u64,
+        executed: u64,
+        total: u64
+    },
+    ScheduleCreated {
+        schedule: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+        interval: i64,
+        end_at: i64
+    },
+    ScheduledPaymentClaimed {
+        schedule: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+        payments_made: u64
+    },
+    ScheduleCancelled {
+        schedule: Pubkey,
+        payments_made: u64
+    },
+    VestingCreated {
+        vesting: Pubkey,
+        recipient: Pubkey,
+        total: u64,
+        start_at: i64,
+        cliff_at: i64,
+        end_at: i64
+    },
+    VestedWithdrawn {
+        vesting: Pubkey,
+        recipient: Pubkey,
+        amount: u64
+    },
+    VestingCancelled {
+        vesting: Pubkey,
+        refunded: u64
+    },
+    SpentWithinLimit {
+        owner: Pubkey,
+        to_address: Pubkey,
+        vault_index: u8,
+        amount: u64
+    },
+    OwnerRegistryFull {
+        owner: Pubkey,
+        wallet: Pubkey
+    }
+}
+
+impl MultiSigWalletEvent {
+    /// Logs the event and chains its borsh encoding into the wallet's audit
+    /// hash, the logged bytes are what an indexer hashes to replay the chain.
+    pub fn emit(&self, account_data: &mut MultiSigWalletState) -> ProgramResult {
+        let event_bytes = self.try_to_vec()?;
+        account_data.audit_hash = hashv(&[&account_data.audit_hash, &event_bytes]).to_bytes();
+
+        msg!("Event: {:?}", self);
+        msg!("Event data: {}", hex::encode(&event_bytes));
+
+        Ok(())
+    }
+}
@@ -1,57 +1,174 @@
 use borsh::{
+    BorshSerialize,
     BorshDeserialize
 };
 use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey
 };
+use crate::state::{
+    BatchActionState,
+    RentPayer,
+    TransactionVariant
+};
 
 pub enum MultiSigWalletInstruction {
-    CreateWallet {
+    CreateWallet(CreateWalletPayload),
+    CreateTransaction(Box<CreateTransactionPayload>),
+    ConfirmTransaction {
+        transaction_id: u64,
+        transaction_hash: [u8; 32]
+    },
+    RejectTransaction {},
+    ExecuteTransaction {
+        transaction_id: u64,
+        transaction_hash: [u8; 32]
+    },
+    CancelTransaction {},
+    SweepLegacyWallet {},
+    InitiateRecovery {
         owners: Vec<Pubkey>,
         threshold: u64
     },
-    CreateTransaction {
-        variant: u8,
+    ApproveRecovery {},
+    ExecuteRecovery {},
+    CancelRecovery {},
+    Pause {},
+    SpendWithinLimit {
         amount: u64,
-        owners: Vec<Pubkey>,
-        threshold: u64
+        vault_index: u8
     },
-    ConfirmTransaction {},
-    RejectTransaction {},
-    ExecuteTransaction {},
-    CancelTransaction {}
+    AppendBatchActions {
+        transaction_id: u64,
+        actions: Vec<BatchActionState>
+    },
+    ClaimScheduledPayment {},
+    WithdrawVested {}
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct CreateWalletPayload {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u64,
+    pub guardians: Vec<Pubkey>,
+    pub guardian_threshold: u64,
+    pub inactivity_period: i64,
+    pub recovery_delay: i64,
+    pub owners_only_execution: bool
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct CreateTransactionPayload {
+    pub variant: TransactionVariant,
+    pub amount: u64,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u64,
+    pub vault_index: u8,
+    pub guardians: Vec<Pubkey>,
+    pub guardian_threshold: u64,
+    pub inactivity_period: i64,
+    pub recovery_delay: i64,
+    pub period: i64,
+    pub allowlist_enabled: bool,
+    pub allowlist: Vec<Pubkey>,
+    pub allowlist_bypass_threshold: u64,
+    pub veto_threshold: u64,
+    pub expires_at: i64,
+    pub owners_only_execution: bool,
+    pub owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub accounts: Vec<Pubkey>,
+    pub authority_type: u8,
+    pub recipient_rent_payer: RentPayer,
+    pub actions: Vec<BatchActionState>,
+    pub batch_chunked: bool,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub cliff_at: i64,
+    pub description: String,
+    pub reference: Option<[u8; 32]>
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct TransactionReferencePayload {
+    pub transaction_id: u64,
+    pub transaction_hash: [u8; 32]
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct InitiateRecoveryPayload {
+    pub owners: Vec<Pubkey>,
+    pub threshold: u64
 }
 
-#[derive(BorshDeserialize)]
-pub struct MultiSigWalletInstructionPayload {
-    variant: u8,
-    amount: u64,
-    owners: Vec<Pubkey>,
-    threshold: u64
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct SpendWithinLimitPayload {
+    pub amount: u64,
+    pub vault_index: u8
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct AppendBatchActionsPayload {
+    pub transaction_id: u64,
+    pub actions: Vec<BatchActionState>
 }
 
 impl MultiSigWalletInstruction {
     pub fn unpack(instruction_data: &[u8]) -> Result<Self, ProgramError> {
         let (&instruction_variant, rest) = instruction_data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
-        let payload = MultiSigWalletInstructionPayload::try_from_slice(rest).unwrap();
 
         Ok(match instruction_variant {
-            0 => Self::CreateWallet {
-                owners: payload.owners,
-                threshold: payload.threshold
-            },
-            1 => Self::CreateTransaction {
-                variant: payload.variant,
-                amount: payload.amount,
-                owners: payload.owners,
-                threshold: payload.threshold
+            0 => Self::CreateWallet(Self::unpack_payload(rest)?),
+            1 => Self::CreateTransaction(Box::new(Self::unpack_payload(rest)?)),
+            2 => {
+                let payload: TransactionReferencePayload = Self::unpack_payload(rest)?;
+                Self::ConfirmTransaction {
+                    transaction_id: payload.transaction_id,
+                    transaction_hash: payload.transaction_hash
+                }
             },
-            2 => Self::ConfirmTransaction {},
             3 => Self::RejectTransaction {},
-            4 => Self::ExecuteTransaction {},
+            4 => {
+                let payload: TransactionReferencePayload = Self::unpack_payload(rest)?;
+                Self::ExecuteTransaction {
+                    transaction_id: payload.transaction_id,
+                    transaction_hash: payload.transaction_hash
+                }
+            },
             5 => Self::CancelTransaction {},
+            6 => Self::SweepLegacyWallet {},
+            7 => {
+                let payload: InitiateRecoveryPayload = Self::unpack_payload(rest)?;
+                Self::InitiateRecovery {
+                    owners: payload.owners,
+                    threshold: payload.threshold
+                }
+            },
+            8 => Self::ApproveRecovery {},
+            9 => Self::ExecuteRecovery {},
+            10 => Self::CancelRecovery {},
+            11 => Self::Pause {},
+            12 => {
+                let payload: SpendWithinLimitPayload = Self::unpack_payload(rest)?;
+                Self::SpendWithinLimit {
+                    amount: payload.amount,
+                    vault_index: payload.vault_index
+                }
+            },
+            13 => {
+                let payload: AppendBatchActionsPayload = Self::unpack_payload(rest)?;
+                Self::AppendBatchActions {
+                    transaction_id: payload.transaction_id,
+                    actions: payload.actions
+                }
+            },
+            14 => Self::ClaimScheduledPayment {},
+            15 => Self::WithdrawVested {},
             _ => return Err(ProgramError::InvalidInstructionData)
         })
     }
-}
\ No newline at end of file
+
+    fn unpack_payload<T: BorshDeserialize>(rest: &[u8]) -> Result<T, ProgramError> {
+        T::try_from_slice(rest).map_err(|_| ProgramError::InvalidInstructionData)
+    }
+}
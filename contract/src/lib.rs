@@ -1,5 +1,6 @@
 pub mod entrypoint;
-mod instruction;
+pub mod instruction;
 mod processor;
-mod state;
+pub mod state;
 mod error;
+pub mod event;
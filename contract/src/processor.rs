@@ -3,78 +3,175 @@ use solana_program::{
         AccountInfo,
         next_account_info
     },
+    instruction::{
+        AccountMeta,
+        Instruction
+    },
+    bpf_loader_upgradeable,
     borsh::try_from_slice_unchecked,
+    clock::Clock,
     entrypoint::ProgramResult,
     pubkey::Pubkey,
-    program::invoke_signed,
+    program::{
+        invoke,
+        invoke_signed
+    },
     program_error::ProgramError,
-    program_pack::IsInitialized,
+    program_pack::{
+        IsInitialized,
+        Pack
+    },
     rent::Rent,
+    stake::{
+        self,
+        state::{
+            Authorized,
+            Lockup,
+            StakeState
+        }
+    },
     sysvar::Sysvar,
     system_instruction,
+    system_program,
     msg
 };
 use borsh::{
     BorshSerialize
 };
+use spl_token::{
+    instruction::AuthorityType,
+    state::Mint
+};
+use spl_associated_token_account::{
+    create_associated_token_account,
+    get_associated_token_address
+};
 use crate::instruction::{
+    CreateTransactionPayload,
+    CreateWalletPayload,
     MultiSigWalletInstruction
 };
 use crate::state::{
-    MultiSigWalletState
+    BatchActionKind,
+    BatchActionState,
+    BatchState,
+    LegacyMultiSigWalletState,
+    MultiSigWalletState,
+    OwnerRegistryState,
+    RentPayer,
+    ScheduledPaymentState,
+    TransactionReceiptState,
+    TransactionState,
+    TransactionStatus,
+    TransactionVariant,
+    VestingState,
+    MAX_ALLOWLIST,
+    MAX_BATCH_ACTION_ACCOUNTS,
+    MAX_BATCH_ACTION_DATA,
+    MAX_BATCH_ACTIONS,
+    MAX_DESCRIPTION_LEN,
+    MAX_GUARDIANS,
+    MAX_OWNERS,
+    MAX_REGISTRY_WALLETS,
+    MAX_TRANSACTION_ACCOUNTS
 };
 use crate::error::{
     MultiSigWalletError
 };
+use crate::event::{
+    MultiSigWalletEvent
+};
+use std::slice::Iter;
 
 pub struct Processor;
 impl Processor {
     pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
         let instruction = MultiSigWalletInstruction::unpack(instruction_data)?;
         match instruction {
-            MultiSigWalletInstruction::CreateWallet { owners, threshold } => {
+            MultiSigWalletInstruction::CreateWallet(payload) => {
                 msg!("Instruction: CreateWallet");
-                Self::create_wallet(program_id, accounts, owners, threshold)
+                Self::create_wallet(program_id, accounts, payload)
             },
-            MultiSigWalletInstruction::CreateTransaction { variant, amount, owners, threshold } => {
+            MultiSigWalletInstruction::CreateTransaction(payload) => {
                 msg!("Instruction: CreateTransaction");
-                Self::create_transaction(program_id, accounts, variant, amount, owners, threshold)
+                Self::create_transaction(program_id, accounts, *payload)
             },
-            MultiSigWalletInstruction::ConfirmTransaction {} => {
+            MultiSigWalletInstruction::ConfirmTransaction { transaction_id, transaction_hash } => {
                 msg!("Instruction: ConfirmTransaction");
-                Self::confirm_transaction(program_id, accounts)
+                Self::confirm_transaction(program_id, accounts, transaction_id, transaction_hash)
             },
             MultiSigWalletInstruction::RejectTransaction {} => {
                 msg!("Instruction: RejectTransaction");
                 Self::reject_transaction(program_id, accounts)
             },
-            MultiSigWalletInstruction::ExecuteTransaction {} => {
+            MultiSigWalletInstruction::ExecuteTransaction { transaction_id, transaction_hash } => {
                 msg!("Instruction: ExecuteTransaction");
-                Self::execute_transaction(program_id, accounts)
+                Self::execute_transaction(program_id, accounts, transaction_id, transaction_hash)
             },
             MultiSigWalletInstruction::CancelTransaction {} => {
                 msg!("Instruction: CancelTransaction");
                 Self::cancel_transaction(program_id, accounts)
             },
+            MultiSigWalletInstruction::SweepLegacyWallet {} => {
+                msg!("Instruction: SweepLegacyWallet");
+                Self::sweep_legacy_wallet(program_id, accounts)
+            },
+            MultiSigWalletInstruction::InitiateRecovery { owners, threshold } => {
+                msg!("Instruction: InitiateRecovery");
+                Self::initiate_recovery(program_id, accounts, owners, threshold)
+            },
+            MultiSigWalletInstruction::ApproveRecovery {} => {
+                msg!("Instruction: ApproveRecovery");
+                Self::approve_recovery(program_id, accounts)
+            },
+            MultiSigWalletInstruction::ExecuteRecovery {} => {
+                msg!("Instruction: ExecuteRecovery");
+                Self::execute_recovery(program_id, accounts)
+            },
+            MultiSigWalletInstruction::CancelRecovery {} => {
+                msg!("Instruction: CancelRecovery");
+                Self::cancel_recovery(program_id, accounts)
+            },
+            MultiSigWalletInstruction::Pause {} => {
+                msg!("Instruction: Pause");
+                Self::pause(program_id, accounts)
+            },
+            MultiSigWalletInstruction::SpendWithinLimit { amount, vault_index } => {
+                msg!("Instruction: SpendWithinLimit");
+                Self::spend_within_limit(program_id, accounts, amount, vault_index)
+            },
+            MultiSigWalletInstruction::AppendBatchActions { transaction_id, actions } => {
+                msg!("Instruction: AppendBatchActions");
+                Self::append_batch_actions(program_id, accounts, transaction_id, actions)
+            },
+            MultiSigWalletInstruction::ClaimScheduledPayment {} => {
+                msg!("Instruction: ClaimScheduledPayment");
+                Self::claim_scheduled_payment(program_id, accounts)
+            },
+            MultiSigWalletInstruction::WithdrawVested {} => {
+                msg!("Instruction: WithdrawVested");
+                Self::withdraw_vested(program_id, accounts)
+            },
         }
     }
 
     fn create_wallet(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        owners: Vec<Pubkey>,
-        threshold: u64
+        payload: CreateWalletPayload
     ) -> ProgramResult {
-        if threshold < 2 || threshold > 3 {
-            msg!("Invalid threshold");
-            return Err(MultiSigWalletError::InvalidThreshold.into())
-        }
+        let CreateWalletPayload { owners, threshold, guardians, guardian_threshold, inactivity_period, recovery_delay, owners_only_execution } = payload;
 
-        if owners.len() < threshold as usize {
+        if owners.len() < 2 || owners.len() > MAX_OWNERS {
             msg!("Invalid owners length");
             return Err(MultiSigWalletError::InvalidOwnersLength.into())
         }
 
+        if !(2..=owners.len() as u64).contains(&threshold) {
+            msg!("Invalid threshold");
+            return Err(MultiSigWalletError::InvalidThreshold.into())
+        }
+
         let account_info_iter = &mut accounts.iter();
 
         let initializer = next_account_info(account_info_iter)?;
@@ -95,7 +192,10 @@ impl Processor {
             return Err(MultiSigWalletError::InvalidPDA.into())
         }
 
-        let account_len: usize = 1 + (4 + (3 * 32)) + 8 + (4 + 32) + 1 + 8 + (4 + (3 * 32)) + (4 + (3 * 32)) + (4 + 32) + 8 + (4 + (3 * 32)) + 8;
+        Self::validate_owners(&owners, client_program_derived_account.key)?;
+        Self::validate_guardians(program_id, client_program_derived_account.key, &guardians, guardian_threshold, inactivity_period, recovery_delay)?;
+
+        let account_len: usize = MultiSigWalletState::LEN;
         let rent = Rent::get()?;
         let rent_lamports = rent.minimum_balance(account_len);
 
@@ -111,7 +211,7 @@ impl Processor {
             &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
         )?;
 
-        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).unwrap();
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
 
         if account_data.is_initialized() {
             msg!("Account already initialized");
@@ -121,7 +221,25 @@ impl Processor {
         account_data.is_initialized = true;
         account_data.owners = owners;
         account_data.threshold = threshold;
-        account_data.transaction.is_executed = true;
+        account_data.transaction.status = TransactionStatus::Empty;
+        account_data.guardians = guardians;
+        account_data.guardian_threshold = guardian_threshold;
+        account_data.inactivity_period = inactivity_period;
+        account_data.recovery_delay = recovery_delay;
+        account_data.owners_only_execution = owners_only_execution;
+        account_data.last_activity = Clock::get()?.unix_timestamp;
+
+        Self::update_owner_registries(program_id, initializer, client_program_derived_account, accounts, &mut account_data, &[])?;
+
+        MultiSigWalletEvent::WalletCreated {
+            owners: account_data.owners.clone(),
+            threshold,
+            guardians: account_data.guardians.clone(),
+            guardian_threshold,
+            inactivity_period,
+            recovery_delay,
+            owners_only_execution
+        }.emit(&mut account_data)?;
 
         account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
 
@@ -131,11 +249,10 @@ impl Processor {
     fn create_transaction(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        variant: u8,
-        amount: u64,
-        owners: Vec<Pubkey>,
-        threshold: u64
+        payload: CreateTransactionPayload
     ) -> ProgramResult {
+        let CreateTransactionPayload { variant, amount, owners, threshold, vault_index, guardians, guardian_threshold, inactivity_period, recovery_delay, period, allowlist_enabled, allowlist, allowlist_bypass_threshold, veto_threshold, expires_at, owners_only_execution, owner, new_owner, accounts: transaction_accounts, authority_type, recipient_rent_payer, actions, batch_chunked, start_at, end_at, cliff_at, description, reference } = payload;
+
         let account_info_iter = &mut accounts.iter();
 
         let initializer = next_account_info(account_info_iter)?;
@@ -160,7 +277,7 @@ impl Processor {
             return Err(MultiSigWalletError::InvalidPDA.into())
         }
 
-        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).unwrap();
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
 
         if !account_data.is_initialized() {
             msg!("Wallet not initialized");
@@ -172,22 +289,79 @@ impl Processor {
             return Err(MultiSigWalletError::InvalidOwner.into());
         }
 
-        if !account_data.transaction.is_executed {
-            msg!("Previous transaction not executed");
+        if account_data.transaction.status.is_pending() && !Self::expire_transaction(program_id, initializer, client_program_derived_account, accounts, &mut account_data)? {
+            msg!("Previous transaction still pending");
             return Err(MultiSigWalletError::UnexpectedTransaction.into());
         }
 
-        account_data.transaction.is_executed = false;
-        account_data.transaction.signers.append(&mut vec![initializer.key.clone()]);
+        if expires_at != 0 && expires_at <= Clock::get()?.unix_timestamp {
+            msg!("Expiry needs to be in the future");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if description.len() > MAX_DESCRIPTION_LEN {
+            msg!("Description too long");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        Self::record_owner_activity(&mut account_data)?;
+
+        account_data.transaction_count += 1;
+        account_data.transaction = TransactionState::default();
+        account_data.transaction.id = account_data.transaction_count;
+        account_data.transaction.status = TransactionStatus::Active;
+        account_data.transaction.proposer = *initializer.key;
+        account_data.transaction.signers.append(&mut vec![*initializer.key]);
         account_data.transaction.to_address = *to_account.key;
         account_data.transaction.variant = variant;
+        account_data.transaction.expires_at = expires_at;
+        account_data.transaction.description = description;
+        account_data.transaction.reference = reference;
 
         match account_data.transaction.variant {
-            0 => Self::set_owners_transaction(client_program_derived_account, &mut account_data, owners),
-            1 => Self::set_threshold_transaction(client_program_derived_account, &mut account_data, threshold),
-            2 => Self::send_transaction(client_program_derived_account, &mut account_data, to_account, amount),
-            _ => return Err(ProgramError::InvalidInstructionData)
-        }
+            TransactionVariant::SetOwners => Self::set_owners_transaction(client_program_derived_account, &mut account_data, owners),
+            TransactionVariant::SetThreshold => Self::set_threshold_transaction(&mut account_data, threshold),
+            TransactionVariant::Send => {
+                let vault = next_account_info(account_info_iter)?;
+                Self::send_transaction(program_id, client_program_derived_account, &mut account_data, to_account, vault, amount, vault_index)
+            },
+            TransactionVariant::SetGuardians => Self::set_guardians_transaction(program_id, client_program_derived_account, &mut account_data, guardians, guardian_threshold, inactivity_period, recovery_delay),
+            TransactionVariant::Unpause => Self::unpause_transaction(&mut account_data),
+            TransactionVariant::SetSpendingLimit => Self::set_spending_limit_transaction(&mut account_data, amount, period),
+            TransactionVariant::SetAllowlist => Self::set_allowlist_transaction(&mut account_data, allowlist_enabled, allowlist, allowlist_bypass_threshold),
+            TransactionVariant::SetVetoThreshold => Self::set_veto_threshold_transaction(&mut account_data, veto_threshold),
+            TransactionVariant::SetExecutionPolicy => Self::set_execution_policy_transaction(&mut account_data, owners_only_execution),
+            TransactionVariant::AddOwner | TransactionVariant::RemoveOwner | TransactionVariant::SwapOwner => Self::change_owner_transaction(client_program_derived_account, &mut account_data, owner, new_owner),
+            TransactionVariant::Reconfigure => Self::reconfigure_transaction(client_program_derived_account, &mut account_data, owners, threshold),
+            TransactionVariant::UpgradeProgram => Self::transaction_accounts_transaction(&mut account_data, transaction_accounts, 3),
+            TransactionVariant::SetUpgradeAuthority => Self::transaction_accounts_transaction(&mut account_data, transaction_accounts, 2),
+            TransactionVariant::DelegateStake | TransactionVariant::DeactivateStake | TransactionVariant::WithdrawStake | TransactionVariant::SplitStake | TransactionVariant::MergeStake => Self::stake_transaction(&mut account_data, transaction_accounts, amount, vault_index),
+            TransactionVariant::MintTokens | TransactionVariant::BurnTokens | TransactionVariant::SetTokenAuthority | TransactionVariant::FreezeTokenAccount | TransactionVariant::ThawTokenAccount => Self::token_authority_transaction(&mut account_data, transaction_accounts, amount, authority_type),
+            TransactionVariant::TransferTokens => Self::transfer_tokens_transaction(client_program_derived_account, &mut account_data, to_account, transaction_accounts, amount, vault_index, recipient_rent_payer),
+            TransactionVariant::WrapSol | TransactionVariant::UnwrapSol => Self::wrap_sol_transaction(&mut account_data, amount, vault_index),
+            TransactionVariant::Batch => {
+                let batch = next_account_info(account_info_iter)?;
+                let system_program = next_account_info(account_info_iter)?;
+                Self::batch_transaction(program_id, initializer, client_program_derived_account, batch, system_program, &mut account_data, actions, batch_chunked)
+            },
+            TransactionVariant::CreateSchedule => Self::create_schedule_transaction(client_program_derived_account, &mut account_data, to_account, amount, vault_index, period, start_at, end_at),
+            TransactionVariant::CancelSchedule => Self::transaction_accounts_transaction(&mut account_data, transaction_accounts, 1),
+            TransactionVariant::CreateVesting => Self::create_vesting_transaction(client_program_derived_account, &mut account_data, to_account, amount, vault_index, start_at, cliff_at, end_at),
+            TransactionVariant::CancelVesting => Self::transaction_accounts_transaction(&mut account_data, transaction_accounts, 1),
+        }?;
+
+        account_data.transaction.hash = account_data.transaction.content_hash()?;
+
+        MultiSigWalletEvent::TransactionCreated {
+            id: account_data.transaction.id,
+            variant: account_data.transaction.variant,
+            proposer: account_data.transaction.proposer,
+            hash: account_data.transaction.hash
+        }.emit(&mut account_data)?;
+
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
     }
 
     fn set_owners_transaction(
@@ -195,186 +369,437 @@ impl Processor {
         account_data: &mut MultiSigWalletState,
         owners: Vec<Pubkey>
     ) -> ProgramResult {
-        if owners.len() < account_data.threshold as usize {
+        if owners.len() < account_data.threshold as usize || owners.len() > MAX_OWNERS {
             msg!("Invalid owners length");
             return Err(MultiSigWalletError::InvalidOwnersLength.into())
         }
 
+        Self::validate_owners(&owners, client_program_derived_account.key)?;
+        Self::validate_owner_thresholds(account_data, owners.len())?;
+
         account_data.transaction.owners = owners;
-        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
 
         Ok(())
     }
 
     fn set_threshold_transaction(
-        client_program_derived_account: &AccountInfo,
         account_data: &mut MultiSigWalletState,
         threshold: u64
     ) -> ProgramResult {
-        if threshold < 2 || threshold > 3 || account_data.owners.len() < threshold as usize {
+        if !(2..=account_data.owners.len() as u64).contains(&threshold) {
             msg!("Invalid threshold");
             return Err(MultiSigWalletError::InvalidThreshold.into())
         }
 
         account_data.transaction.threshold = threshold;
-        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
 
         Ok(())
     }
 
     fn send_transaction(
+        program_id: &Pubkey,
         client_program_derived_account: &AccountInfo,
         account_data: &mut MultiSigWalletState,
         to_account: &AccountInfo,
-        amount: u64
+        vault: &AccountInfo,
+        amount: u64,
+        vault_index: u8
     ) -> ProgramResult {
-        if amount <= 0 {
+        let (vault_address, _bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid seeds for vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        if amount == 0 {
             msg!("Amount needs to be higher than 0");
             return Err(ProgramError::InvalidInstructionData)
         }
 
-        if client_program_derived_account.lamports() <= amount {
-            msg!("PDA has insufficient funds");
+        if vault.lamports() < amount {
+            msg!("Vault has insufficient funds");
             return Err(ProgramError::InsufficientFunds)
         }
 
-        if *to_account.key == *client_program_derived_account.key {
+        if *to_account.key == *client_program_derived_account.key || *to_account.key == *vault.key {
             msg!("Cannot send to Self");
             return Err(ProgramError::InvalidInstructionData)
         }
 
+        if !Self::is_allowed_destination(account_data, to_account.key) && account_data.allowlist_bypass_threshold == 0 {
+            msg!("Destination not in the allowlist");
+            return Err(MultiSigWalletError::DestinationNotAllowed.into())
+        }
+
         account_data.transaction.amount = amount;
-        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+        account_data.transaction.vault_index = vault_index;
 
         Ok(())
     }
 
-    fn confirm_transaction(
+    fn set_guardians_transaction(
         program_id: &Pubkey,
-        accounts: &[AccountInfo]
+        client_program_derived_account: &AccountInfo,
+        account_data: &mut MultiSigWalletState,
+        guardians: Vec<Pubkey>,
+        guardian_threshold: u64,
+        inactivity_period: i64,
+        recovery_delay: i64
     ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
+        Self::validate_guardians(program_id, client_program_derived_account.key, &guardians, guardian_threshold, inactivity_period, recovery_delay)?;
 
-        let initializer = next_account_info(account_info_iter)?;
-        let base = next_account_info(account_info_iter)?;
-        let client_program_derived_account = next_account_info(account_info_iter)?;
+        account_data.transaction.guardians = guardians;
+        account_data.transaction.guardian_threshold = guardian_threshold;
+        account_data.transaction.inactivity_period = inactivity_period;
+        account_data.transaction.recovery_delay = recovery_delay;
 
-        if client_program_derived_account.owner != program_id {
-            msg!("PDA not owned by the program");
-            return Err(ProgramError::IllegalOwner)
+        Ok(())
+    }
+
+    fn unpause_transaction(
+        account_data: &mut MultiSigWalletState
+    ) -> ProgramResult {
+        if !account_data.is_paused {
+            msg!("Wallet not paused");
+            return Err(MultiSigWalletError::InvalidInstruction.into())
         }
 
-        if !initializer.is_signer {
-            msg!("Missing required signature");
-            return Err(ProgramError::MissingRequiredSignature)
+        Ok(())
+    }
+
+    fn set_spending_limit_transaction(
+        account_data: &mut MultiSigWalletState,
+        amount: u64,
+        period: i64
+    ) -> ProgramResult {
+        if amount > 0 && period <= 0 {
+            msg!("Spending limit period needs to be higher than 0");
+            return Err(ProgramError::InvalidInstructionData)
         }
 
-        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+        account_data.transaction.amount = amount;
+        account_data.transaction.period = period;
 
-        if program_derived_account != *client_program_derived_account.key {
-            msg!("Invalid seeds for PDA");
-            return Err(MultiSigWalletError::InvalidPDA.into())
+        Ok(())
+    }
+
+    fn set_allowlist_transaction(
+        account_data: &mut MultiSigWalletState,
+        allowlist_enabled: bool,
+        allowlist: Vec<Pubkey>,
+        allowlist_bypass_threshold: u64
+    ) -> ProgramResult {
+        if allowlist.len() > MAX_ALLOWLIST {
+            msg!("Invalid allowlist length");
+            return Err(MultiSigWalletError::InvalidAllowlist.into())
         }
 
-        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).unwrap();
+        if allowlist_bypass_threshold != 0 && (allowlist_bypass_threshold <= account_data.threshold || account_data.owners.len() < allowlist_bypass_threshold as usize) {
+            msg!("Invalid allowlist bypass threshold");
+            return Err(MultiSigWalletError::InvalidAllowlist.into())
+        }
 
-        msg!("account_data ${:?}", account_data);
+        account_data.transaction.allowlist_enabled = allowlist_enabled;
+        account_data.transaction.allowlist = allowlist;
+        account_data.transaction.allowlist_bypass_threshold = allowlist_bypass_threshold;
 
-        if !account_data.is_initialized() {
-            msg!("Wallet not initialized");
-            return Err(MultiSigWalletError::UninitializedAccount.into());
+        Ok(())
+    }
+
+    fn set_veto_threshold_transaction(
+        account_data: &mut MultiSigWalletState,
+        veto_threshold: u64
+    ) -> ProgramResult {
+        if account_data.owners.len() < veto_threshold as usize {
+            msg!("Invalid veto threshold");
+            return Err(MultiSigWalletError::InvalidThreshold.into())
         }
 
-        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
-            msg!("Initializer not an owner");
-            return Err(MultiSigWalletError::InvalidOwner.into());
+        account_data.transaction.veto_threshold = veto_threshold;
+
+        Ok(())
+    }
+
+    fn set_execution_policy_transaction(
+        account_data: &mut MultiSigWalletState,
+        owners_only_execution: bool
+    ) -> ProgramResult {
+        account_data.transaction.owners_only_execution = owners_only_execution;
+
+        Ok(())
+    }
+
+    fn change_owner_transaction(
+        client_program_derived_account: &AccountInfo,
+        account_data: &mut MultiSigWalletState,
+        owner: Pubkey,
+        new_owner: Pubkey
+    ) -> ProgramResult {
+        account_data.transaction.owner = owner;
+        account_data.transaction.new_owner = new_owner;
+        Self::changed_owners(account_data, client_program_derived_account.key)?;
+
+        Ok(())
+    }
+
+    fn reconfigure_transaction(
+        client_program_derived_account: &AccountInfo,
+        account_data: &mut MultiSigWalletState,
+        owners: Vec<Pubkey>,
+        threshold: u64
+    ) -> ProgramResult {
+        if owners.len() < 2 || owners.len() > MAX_OWNERS {
+            msg!("Invalid owners length");
+            return Err(MultiSigWalletError::InvalidOwnersLength.into())
         }
 
-        if account_data.transaction.is_executed {
-            msg!("Transaction has been executed already");
-            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        if !(2..=owners.len() as u64).contains(&threshold) {
+            msg!("Invalid threshold");
+            return Err(MultiSigWalletError::InvalidThreshold.into())
         }
 
-        if account_data.transaction.signers.iter().any(|owner| owner == initializer.key) {
-            msg!("Initializer already signed the transaction");
-            return Err(MultiSigWalletError::InvalidInstruction.into());
+        Self::validate_owners(&owners, client_program_derived_account.key)?;
+        Self::validate_owner_thresholds(account_data, owners.len())?;
+
+        account_data.transaction.owners = owners;
+        account_data.transaction.threshold = threshold;
+
+        Ok(())
+    }
+
+    fn transaction_accounts_transaction(
+        account_data: &mut MultiSigWalletState,
+        transaction_accounts: Vec<Pubkey>,
+        expected_len: usize
+    ) -> ProgramResult {
+        if transaction_accounts.len() != expected_len || transaction_accounts.len() > MAX_TRANSACTION_ACCOUNTS {
+            msg!("Invalid transaction accounts length");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
         }
 
-        account_data.transaction.opponents.retain(|owner| owner != initializer.key);
-        account_data.transaction.signers.append(&mut vec![initializer.key.clone()]);
-        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+        account_data.transaction.accounts = transaction_accounts;
 
         Ok(())
     }
 
-    fn reject_transaction(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo]
+    fn stake_transaction(
+        account_data: &mut MultiSigWalletState,
+        transaction_accounts: Vec<Pubkey>,
+        amount: u64,
+        vault_index: u8
     ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
+        let expected_len = match account_data.transaction.variant {
+            TransactionVariant::MergeStake => 2,
+            _ => 1
+        };
 
-        let initializer = next_account_info(account_info_iter)?;
-        let base = next_account_info(account_info_iter)?;
-        let client_program_derived_account = next_account_info(account_info_iter)?;
+        Self::transaction_accounts_transaction(account_data, transaction_accounts, expected_len)?;
 
-        if client_program_derived_account.owner != program_id {
-            msg!("PDA not owned by the program");
-            return Err(ProgramError::IllegalOwner)
+        let moves_lamports = account_data.transaction.variant == TransactionVariant::DelegateStake
+            || account_data.transaction.variant == TransactionVariant::WithdrawStake
+            || account_data.transaction.variant == TransactionVariant::SplitStake;
+
+        if moves_lamports && amount == 0 {
+            msg!("Amount must be greater than zero");
+            return Err(ProgramError::InvalidInstructionData)
         }
 
-        if !initializer.is_signer {
-            msg!("Missing required signature");
-            return Err(ProgramError::MissingRequiredSignature)
+        account_data.transaction.amount = amount;
+        account_data.transaction.vault_index = vault_index;
+
+        Ok(())
+    }
+
+    fn token_authority_transaction(
+        account_data: &mut MultiSigWalletState,
+        transaction_accounts: Vec<Pubkey>,
+        amount: u64,
+        authority_type: u8
+    ) -> ProgramResult {
+        Self::transaction_accounts_transaction(account_data, transaction_accounts, 2)?;
+
+        let moves_tokens = account_data.transaction.variant == TransactionVariant::MintTokens
+            || account_data.transaction.variant == TransactionVariant::BurnTokens;
+
+        if moves_tokens && amount == 0 {
+            msg!("Amount must be greater than zero");
+            return Err(ProgramError::InvalidInstructionData)
         }
 
-        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+        if account_data.transaction.variant == TransactionVariant::SetTokenAuthority {
+            Self::token_authority_type(authority_type)?;
+        }
 
-        if program_derived_account != *client_program_derived_account.key {
-            msg!("Invalid seeds for PDA");
-            return Err(MultiSigWalletError::InvalidPDA.into())
+        account_data.transaction.amount = amount;
+        account_data.transaction.authority_type = authority_type;
+
+        Ok(())
+    }
+
+    fn transfer_tokens_transaction(
+        client_program_derived_account: &AccountInfo,
+        account_data: &mut MultiSigWalletState,
+        to_account: &AccountInfo,
+        transaction_accounts: Vec<Pubkey>,
+        amount: u64,
+        vault_index: u8,
+        recipient_rent_payer: RentPayer
+    ) -> ProgramResult {
+        Self::transaction_accounts_transaction(account_data, transaction_accounts, 1)?;
+
+        if amount == 0 {
+            msg!("Amount needs to be higher than 0");
+            return Err(ProgramError::InvalidInstructionData)
         }
 
-        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).unwrap();
+        if *to_account.key == *client_program_derived_account.key {
+            msg!("Cannot send to Self");
+            return Err(ProgramError::InvalidInstructionData)
+        }
 
-        if !account_data.is_initialized() {
-            msg!("Wallet not initialized");
-            return Err(MultiSigWalletError::UninitializedAccount.into());
+        if !Self::is_allowed_destination(account_data, to_account.key) && account_data.allowlist_bypass_threshold == 0 {
+            msg!("Destination not in the allowlist");
+            return Err(MultiSigWalletError::DestinationNotAllowed.into())
         }
 
-        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
-            msg!("Initializer not an owner");
-            return Err(MultiSigWalletError::InvalidOwner.into());
+        account_data.transaction.amount = amount;
+        account_data.transaction.vault_index = vault_index;
+        account_data.transaction.recipient_rent_payer = recipient_rent_payer;
+
+        Ok(())
+    }
+
+    fn wrap_sol_transaction(
+        account_data: &mut MultiSigWalletState,
+        amount: u64,
+        vault_index: u8
+    ) -> ProgramResult {
+        if account_data.transaction.variant == TransactionVariant::WrapSol && amount == 0 {
+            msg!("Amount needs to be higher than 0");
+            return Err(ProgramError::InvalidInstructionData)
         }
 
-        if account_data.transaction.is_executed {
-            msg!("Transaction has been executed already");
-            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        account_data.transaction.amount = amount;
+        account_data.transaction.vault_index = vault_index;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn batch_transaction<'a>(
+        program_id: &Pubkey,
+        initializer: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        batch: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        account_data: &mut MultiSigWalletState,
+        actions: Vec<BatchActionState>,
+        batch_chunked: bool
+    ) -> ProgramResult {
+        Self::validate_batch_actions(client_program_derived_account.key, account_data, &actions, 0)?;
+
+        let transaction_id = account_data.transaction.id.to_le_bytes();
+        let (batch_address, bump_seed) = Pubkey::find_program_address(&[b"batch".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id], program_id);
+
+        if batch_address != *batch.key {
+            msg!("Invalid seeds for batch");
+            return Err(MultiSigWalletError::InvalidPDA.into())
         }
 
-        if account_data.transaction.opponents.iter().any(|owner| owner == initializer.key) {
-            msg!("Initializer already rejected the transaction");
-            return Err(MultiSigWalletError::InvalidInstruction.into());
+        Self::create_program_account(initializer, batch, system_program, BatchState::LEN, program_id, &[&[b"batch".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id, &[bump_seed]]])?;
+
+        let batch_data = BatchState {
+            is_initialized: true,
+            wallet: *client_program_derived_account.key,
+            transaction_id: account_data.transaction.id,
+            actions
+        };
+
+        account_data.transaction.batch_hash = batch_data.actions_hash()?;
+        account_data.transaction.batch_chunked = batch_chunked;
+        batch_data.serialize(&mut &mut batch.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn validate_batch_actions(
+        wallet: &Pubkey,
+        account_data: &MultiSigWalletState,
+        actions: &[BatchActionState],
+        existing_actions: usize
+    ) -> ProgramResult {
+        if existing_actions + actions.len() > MAX_BATCH_ACTIONS {
+            msg!("Too many batch actions");
+            return Err(MultiSigWalletError::InvalidBatch.into())
         }
 
-        account_data.transaction.signers.retain(|owner| owner != initializer.key);
-        account_data.transaction.opponents.append(&mut vec![initializer.key.clone()]);
-        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+        for action in actions.iter() {
+            if action.accounts.len() > MAX_BATCH_ACTION_ACCOUNTS || action.data.len() > MAX_BATCH_ACTION_DATA {
+                msg!("Batch action accounts or data too long");
+                return Err(MultiSigWalletError::InvalidBatch.into())
+            }
+
+            match action.kind {
+                BatchActionKind::Send | BatchActionKind::TransferTokens => {
+                    if action.amount == 0 {
+                        msg!("Amount needs to be higher than 0");
+                        return Err(MultiSigWalletError::InvalidBatch.into())
+                    }
+
+                    if action.to_address == *wallet {
+                        msg!("Cannot send to Self");
+                        return Err(MultiSigWalletError::InvalidBatch.into())
+                    }
+
+                    if !Self::is_allowed_destination(account_data, &action.to_address) && account_data.allowlist_bypass_threshold == 0 {
+                        msg!("Destination not in the allowlist");
+                        return Err(MultiSigWalletError::DestinationNotAllowed.into())
+                    }
+                },
+                BatchActionKind::SetThreshold => {
+                    if action.amount < 2 || action.amount > 3 || account_data.owners.len() < action.amount as usize {
+                        msg!("Invalid threshold");
+                        return Err(MultiSigWalletError::InvalidThreshold.into())
+                    }
+                },
+                BatchActionKind::SetVetoThreshold => {
+                    if account_data.owners.len() < action.amount as usize {
+                        msg!("Invalid veto threshold");
+                        return Err(MultiSigWalletError::InvalidThreshold.into())
+                    }
+                },
+                BatchActionKind::Invoke => {
+                    if action.accounts.iter().any(|meta| meta.is_signer && meta.pubkey != *wallet) {
+                        msg!("Only the wallet can sign batch instructions");
+                        return Err(MultiSigWalletError::InvalidBatch.into())
+                    }
+
+                    if account_data.allowlist_enabled && account_data.allowlist_bypass_threshold == 0 {
+                        msg!("Batch instructions not allowed with the allowlist enabled");
+                        return Err(MultiSigWalletError::DestinationNotAllowed.into())
+                    }
+                }
+            }
+        }
 
         Ok(())
     }
 
-    fn execute_transaction(
+    fn append_batch_actions(
         program_id: &Pubkey,
-        accounts: &[AccountInfo]
+        accounts: &[AccountInfo],
+        transaction_id: u64,
+        actions: Vec<BatchActionState>
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
 
         let initializer = next_account_info(account_info_iter)?;
         let base = next_account_info(account_info_iter)?;
         let client_program_derived_account = next_account_info(account_info_iter)?;
-        let to_account = next_account_info(account_info_iter)?;
+        let batch = next_account_info(account_info_iter)?;
 
-        if client_program_derived_account.owner != program_id {
+        if client_program_derived_account.owner != program_id || batch.owner != program_id {
             msg!("PDA not owned by the program");
             return Err(ProgramError::IllegalOwner)
         }
@@ -391,78 +816,259 @@ impl Processor {
             return Err(MultiSigWalletError::InvalidPDA.into())
         }
 
-        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).unwrap();
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
 
         if !account_data.is_initialized() {
             msg!("Wallet not initialized");
             return Err(MultiSigWalletError::UninitializedAccount.into());
         }
 
-        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
-            msg!("Initializer not an owner");
-            return Err(MultiSigWalletError::InvalidOwner.into());
+        if !account_data.transaction.status.is_pending() || account_data.transaction.variant != TransactionVariant::Batch {
+            msg!("No pending batch transaction");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
         }
 
-        if account_data.transaction.is_executed {
-            msg!("Transaction has been executed already");
-            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        if account_data.transaction.id != transaction_id {
+            msg!("Transaction does not match the reviewed one");
+            return Err(MultiSigWalletError::TransactionMismatch.into());
         }
 
-        if account_data.transaction.to_address != *to_account.key {
-            msg!("The provided address does not match with the stored address");
-            return Err(MultiSigWalletError::InvalidInstruction.into());
+        if account_data.transaction.proposer != *initializer.key {
+            msg!("Only the proposer can append batch actions");
+            return Err(MultiSigWalletError::InvalidOwner.into());
         }
 
-        if account_data.transaction.signers.len() < account_data.threshold as usize {
-            msg!("Not enough approvals");
-            return Err(MultiSigWalletError::NotEnoughApprovals.into());
+        if Self::expire_transaction(program_id, initializer, client_program_derived_account, accounts, &mut account_data)? {
+            return Ok(())
         }
 
-        match account_data.transaction.variant {
-            0 => Self::set_owners(client_program_derived_account, &mut account_data),
-            1 => Self::set_threshold(client_program_derived_account, &mut account_data),
-            2 => Self::send(client_program_derived_account, &mut account_data, to_account),
-            _ => return Err(ProgramError::InvalidInstructionData)
+        if account_data.transaction.batch_progress > 0 {
+            msg!("Batch already partially executed");
+            return Err(MultiSigWalletError::InvalidBatch.into());
         }
-    }
 
-    fn set_owners(
-        client_program_derived_account: &AccountInfo,
-        account_data: &mut MultiSigWalletState,
-    ) -> ProgramResult {
-        account_data.owners = account_data.transaction.owners.clone();
-        Self::clear_transaction_state(account_data);
+        let mut batch_data = Self::load_batch(program_id, client_program_derived_account, batch, &account_data)?;
+
+        Self::validate_batch_actions(client_program_derived_account.key, &account_data, &actions, batch_data.actions.len())?;
+
+        batch_data.actions.extend(actions);
+        batch_data.serialize(&mut &mut batch.data.borrow_mut()[..])?;
+
+        account_data.transaction.batch_hash = batch_data.actions_hash()?;
+        account_data.transaction.status = TransactionStatus::Active;
+        account_data.transaction.signers = vec![*initializer.key];
+        account_data.transaction.opponents = Vec::new();
+        account_data.transaction.cancellers = Vec::new();
+        account_data.transaction.hash = account_data.transaction.content_hash()?;
+
+        MultiSigWalletEvent::BatchActionsAppended {
+            id: account_data.transaction.id,
+            actions: batch_data.actions.len() as u64,
+            hash: account_data.transaction.hash
+        }.emit(&mut account_data)?;
         account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
 
         Ok(())
     }
 
-    fn set_threshold(
+    fn load_batch(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo,
+        batch: &AccountInfo,
+        account_data: &MultiSigWalletState
+    ) -> Result<BatchState, ProgramError> {
+        let (batch_address, _bump_seed) = Pubkey::find_program_address(&[b"batch".as_ref(), client_program_derived_account.key.as_ref(), &account_data.transaction.id.to_le_bytes()], program_id);
+
+        if batch_address != *batch.key || batch.owner != program_id {
+            msg!("Invalid seeds for batch");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let batch_data = try_from_slice_unchecked::<BatchState>(&batch.data.borrow()).unwrap();
+
+        if !batch_data.is_initialized() {
+            msg!("Batch not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        Ok(batch_data)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_schedule_transaction(
         client_program_derived_account: &AccountInfo,
         account_data: &mut MultiSigWalletState,
+        to_account: &AccountInfo,
+        amount: u64,
+        vault_index: u8,
+        period: i64,
+        start_at: i64,
+        end_at: i64
     ) -> ProgramResult {
-        account_data.threshold = account_data.transaction.threshold;
-        Self::clear_transaction_state(account_data);
-        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+        if amount == 0 || period <= 0 || start_at < 0 {
+            msg!("Invalid schedule amount, interval or start");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if end_at <= Clock::get()?.unix_timestamp || end_at < start_at {
+            msg!("Schedule end needs to be in the future and after its start");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if *to_account.key == *client_program_derived_account.key {
+            msg!("Cannot send to Self");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if !Self::is_allowed_destination(account_data, to_account.key) && account_data.allowlist_bypass_threshold == 0 {
+            msg!("Destination not in the allowlist");
+            return Err(MultiSigWalletError::DestinationNotAllowed.into())
+        }
+
+        account_data.transaction.amount = amount;
+        account_data.transaction.vault_index = vault_index;
+        account_data.transaction.period = period;
+        account_data.transaction.start_at = start_at;
+        account_data.transaction.end_at = end_at;
 
         Ok(())
     }
 
-    fn send(
+    #[allow(clippy::too_many_arguments)]
+    fn create_vesting_transaction(
         client_program_derived_account: &AccountInfo,
         account_data: &mut MultiSigWalletState,
         to_account: &AccountInfo,
+        amount: u64,
+        vault_index: u8,
+        start_at: i64,
+        cliff_at: i64,
+        end_at: i64
+    ) -> ProgramResult {
+        if amount == 0 {
+            msg!("Amount needs to be higher than 0");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if start_at <= 0 || cliff_at < start_at || end_at < cliff_at || end_at <= start_at || end_at <= Clock::get()?.unix_timestamp {
+            msg!("Invalid vesting start, cliff or end");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if *to_account.key == *client_program_derived_account.key {
+            msg!("Cannot send to Self");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if !Self::is_allowed_destination(account_data, to_account.key) && account_data.allowlist_bypass_threshold == 0 {
+            msg!("Destination not in the allowlist");
+            return Err(MultiSigWalletError::DestinationNotAllowed.into())
+        }
+
+        account_data.transaction.amount = amount;
+        account_data.transaction.vault_index = vault_index;
+        account_data.transaction.start_at = start_at;
+        account_data.transaction.cliff_at = cliff_at;
+        account_data.transaction.end_at = end_at;
+
+        Ok(())
+    }
+
+    fn token_authority_type(
+        authority_type: u8
+    ) -> Result<AuthorityType, ProgramError> {
+        match authority_type {
+            0 => Ok(AuthorityType::MintTokens),
+            1 => Ok(AuthorityType::FreezeAccount),
+            2 => Ok(AuthorityType::AccountOwner),
+            3 => Ok(AuthorityType::CloseAccount),
+            _ => {
+                msg!("Invalid token authority type");
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+    }
+
+    fn confirm_transaction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        transaction_id: u64,
+        transaction_hash: [u8; 32]
     ) -> ProgramResult {
-        **client_program_derived_account.try_borrow_mut_lamports()? -= account_data.transaction.amount;
-        **to_account.try_borrow_mut_lamports()? += account_data.transaction.amount;
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
+            msg!("Initializer not an owner");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        if !account_data.transaction.status.is_pending() {
+            msg!("No pending transaction");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        if account_data.transaction.id != transaction_id || account_data.transaction.hash != transaction_hash {
+            msg!("Transaction does not match the reviewed one");
+            return Err(MultiSigWalletError::TransactionMismatch.into());
+        }
+
+        if Self::expire_transaction(program_id, initializer, client_program_derived_account, accounts, &mut account_data)? {
+            return Ok(())
+        }
+
+        if account_data.transaction.signers.iter().any(|owner| owner == initializer.key) {
+            msg!("Initializer already signed the transaction");
+            return Err(MultiSigWalletError::InvalidInstruction.into());
+        }
+
+        Self::record_owner_activity(&mut account_data)?;
+
+        account_data.transaction.opponents.retain(|owner| owner != initializer.key);
+        account_data.transaction.signers.append(&mut vec![*initializer.key]);
+
+        if Self::current_approvals(&account_data) >= account_data.threshold as usize {
+            account_data.transaction.status = TransactionStatus::Approved;
+        }
+
+        MultiSigWalletEvent::TransactionConfirmed {
+            id: account_data.transaction.id,
+            owner: *initializer.key
+        }.emit(&mut account_data)?;
 
-        Self::clear_transaction_state(account_data);
         account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
 
         Ok(())
     }
 
-    fn cancel_transaction(
+    fn reject_transaction(
         program_id: &Pubkey,
         accounts: &[AccountInfo]
     ) -> ProgramResult {
@@ -471,6 +1077,8 @@ impl Processor {
         let initializer = next_account_info(account_info_iter)?;
         let base = next_account_info(account_info_iter)?;
         let client_program_derived_account = next_account_info(account_info_iter)?;
+        let receipt = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
 
         if client_program_derived_account.owner != program_id {
             msg!("PDA not owned by the program");
@@ -489,7 +1097,7 @@ impl Processor {
             return Err(MultiSigWalletError::InvalidPDA.into())
         }
 
-        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).unwrap();
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
 
         if !account_data.is_initialized() {
             msg!("Wallet not initialized");
@@ -501,25 +1109,2557 @@ impl Processor {
             return Err(MultiSigWalletError::InvalidOwner.into());
         }
 
-        if account_data.transaction.is_executed {
-            msg!("Transaction has been executed already");
+        if !account_data.transaction.status.is_pending() {
+            msg!("No pending transaction");
             return Err(MultiSigWalletError::UnexpectedInstruction.into());
         }
 
-        Self::clear_transaction_state(&mut account_data);
+        if Self::expire_transaction(program_id, initializer, client_program_derived_account, accounts, &mut account_data)? {
+            return Ok(())
+        }
+
+        if account_data.transaction.batch_progress > 0 {
+            msg!("Batch partially executed, it can only be completed");
+            return Err(MultiSigWalletError::InvalidBatch.into());
+        }
+
+        if account_data.transaction.opponents.iter().any(|owner| owner == initializer.key) {
+            msg!("Initializer already rejected the transaction");
+            return Err(MultiSigWalletError::InvalidInstruction.into());
+        }
+
+        Self::record_owner_activity(&mut account_data)?;
+
+        account_data.transaction.signers.retain(|owner| owner != initializer.key);
+        account_data.transaction.opponents.append(&mut vec![*initializer.key]);
+
+        if Self::current_approvals(&account_data) < account_data.threshold as usize {
+            account_data.transaction.status = TransactionStatus::Active;
+        }
+
+        MultiSigWalletEvent::TransactionRejected {
+            id: account_data.transaction.id,
+            owner: *initializer.key
+        }.emit(&mut account_data)?;
+
+        let opponents = account_data.transaction.opponents.len();
+        let is_vetoed = account_data.veto_threshold > 0 && opponents >= account_data.veto_threshold as usize;
+
+        if is_vetoed || account_data.owners.len().saturating_sub(opponents) < account_data.threshold as usize {
+            msg!("Transaction can no longer pass, rejecting it");
+            account_data.transaction.status = TransactionStatus::Rejected;
+            Self::emit_transaction_closed(&mut account_data)?;
+            Self::write_receipt(program_id, initializer, client_program_derived_account, receipt, system_program, &account_data)?;
+        }
+
         account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
 
         Ok(())
     }
 
-    fn clear_transaction_state(
+    fn execute_transaction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        transaction_id: u64,
+        transaction_hash: [u8; 32]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+        let to_account = next_account_info(account_info_iter)?;
+        let receipt = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        let is_owner = account_data.owners.iter().any(|owner| owner == initializer.key);
+
+        if account_data.owners_only_execution && !is_owner {
+            msg!("Initializer not an owner");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        if !account_data.transaction.status.is_pending() {
+            msg!("No pending transaction");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        if account_data.transaction.id != transaction_id || account_data.transaction.hash != transaction_hash {
+            msg!("Transaction does not match the reviewed one");
+            return Err(MultiSigWalletError::TransactionMismatch.into());
+        }
+
+        if Self::expire_transaction(program_id, initializer, client_program_derived_account, accounts, &mut account_data)? {
+            return Ok(())
+        }
+
+        if account_data.transaction.to_address != *to_account.key {
+            msg!("The provided address does not match with the stored address");
+            return Err(MultiSigWalletError::InvalidInstruction.into());
+        }
+
+        if Self::current_approvals(&account_data) < account_data.threshold as usize {
+            msg!("Not enough approvals");
+            return Err(MultiSigWalletError::NotEnoughApprovals.into());
+        }
+
+        // Owner and guardian changes stay executable while paused, so a rogue
+        // owner or guardian that keeps pausing the wallet can be removed.
+        if account_data.is_paused && !matches!(account_data.transaction.variant, TransactionVariant::SetOwners | TransactionVariant::AddOwner | TransactionVariant::RemoveOwner | TransactionVariant::SwapOwner | TransactionVariant::Reconfigure | TransactionVariant::SetGuardians | TransactionVariant::Unpause) {
+            msg!("Wallet paused, only owner, guardian and unpause transactions can be executed");
+            return Err(MultiSigWalletError::WalletPaused.into());
+        }
+
+        if is_owner {
+            Self::record_owner_activity(&mut account_data)?;
+        }
+
+        let previous_owners = account_data.owners.clone();
+
+        if account_data.transaction.variant == TransactionVariant::Batch {
+            let batch = next_account_info(account_info_iter)?;
+
+            if !Self::execute_batch(program_id, base, client_program_derived_account, bump_seed, &mut account_data, batch, system_program, account_info_iter)? {
+                account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+                return Ok(())
+            }
+        }
+
+        match account_data.transaction.variant {
+            TransactionVariant::SetOwners => Self::set_owners(&mut account_data),
+            TransactionVariant::SetThreshold => Self::set_threshold(&mut account_data),
+            TransactionVariant::Send => {
+                let vault = next_account_info(account_info_iter)?;
+                Self::send(program_id, client_program_derived_account, &mut account_data, to_account, vault, system_program)
+            },
+            TransactionVariant::SetGuardians => Self::set_guardians(&mut account_data),
+            TransactionVariant::Unpause => Self::unpause(&mut account_data),
+            TransactionVariant::SetSpendingLimit => Self::set_spending_limit(&mut account_data),
+            TransactionVariant::SetAllowlist => Self::set_allowlist(&mut account_data),
+            TransactionVariant::SetVetoThreshold => Self::set_veto_threshold(&mut account_data),
+            TransactionVariant::SetExecutionPolicy => Self::set_execution_policy(&mut account_data),
+            TransactionVariant::AddOwner | TransactionVariant::RemoveOwner | TransactionVariant::SwapOwner => Self::change_owner(client_program_derived_account, &mut account_data),
+            TransactionVariant::Reconfigure => Self::reconfigure(&mut account_data),
+            TransactionVariant::UpgradeProgram => {
+                let program = next_account_info(account_info_iter)?;
+                let programdata = next_account_info(account_info_iter)?;
+                let buffer = next_account_info(account_info_iter)?;
+                let spill = next_account_info(account_info_iter)?;
+                let rent = next_account_info(account_info_iter)?;
+                let clock = next_account_info(account_info_iter)?;
+                let loader = next_account_info(account_info_iter)?;
+                Self::upgrade_program(base, client_program_derived_account, bump_seed, &account_data, program, programdata, buffer, spill, rent, clock, loader)
+            },
+            TransactionVariant::SetUpgradeAuthority => {
+                let programdata = next_account_info(account_info_iter)?;
+                let new_authority = next_account_info(account_info_iter)?;
+                let loader = next_account_info(account_info_iter)?;
+                Self::set_upgrade_authority(base, client_program_derived_account, bump_seed, &account_data, programdata, new_authority, loader)
+            },
+            TransactionVariant::DelegateStake => {
+                let vault = next_account_info(account_info_iter)?;
+                let stake_account = next_account_info(account_info_iter)?;
+                let vote = next_account_info(account_info_iter)?;
+                let clock = next_account_info(account_info_iter)?;
+                let stake_history = next_account_info(account_info_iter)?;
+                let stake_config = next_account_info(account_info_iter)?;
+                let rent = next_account_info(account_info_iter)?;
+                let stake_program = next_account_info(account_info_iter)?;
+                Self::delegate_stake(program_id, base, client_program_derived_account, bump_seed, &mut account_data, vault, stake_account, vote, clock, stake_history, stake_config, rent, system_program, stake_program)
+            },
+            TransactionVariant::DeactivateStake => {
+                let stake_account = next_account_info(account_info_iter)?;
+                let clock = next_account_info(account_info_iter)?;
+                let stake_program = next_account_info(account_info_iter)?;
+                Self::deactivate_stake(base, client_program_derived_account, bump_seed, &mut account_data, stake_account, clock, stake_program)
+            },
+            TransactionVariant::WithdrawStake => {
+                let stake_account = next_account_info(account_info_iter)?;
+                let vault = next_account_info(account_info_iter)?;
+                let clock = next_account_info(account_info_iter)?;
+                let stake_history = next_account_info(account_info_iter)?;
+                let stake_program = next_account_info(account_info_iter)?;
+                Self::withdraw_stake(program_id, base, client_program_derived_account, bump_seed, &mut account_data, stake_account, vault, clock, stake_history, stake_program)
+            },
+            TransactionVariant::SplitStake => {
+                let stake_account = next_account_info(account_info_iter)?;
+                let split_stake_account = next_account_info(account_info_iter)?;
+                let stake_program = next_account_info(account_info_iter)?;
+                Self::split_stake(program_id, base, client_program_derived_account, bump_seed, &mut account_data, stake_account, split_stake_account, system_program, stake_program)
+            },
+            TransactionVariant::MergeStake => {
+                let destination_stake_account = next_account_info(account_info_iter)?;
+                let source_stake_account = next_account_info(account_info_iter)?;
+                let clock = next_account_info(account_info_iter)?;
+                let stake_history = next_account_info(account_info_iter)?;
+                let stake_program = next_account_info(account_info_iter)?;
+                Self::merge_stake(base, client_program_derived_account, bump_seed, &mut account_data, destination_stake_account, source_stake_account, clock, stake_history, stake_program)
+            },
+            TransactionVariant::MintTokens | TransactionVariant::BurnTokens | TransactionVariant::SetTokenAuthority | TransactionVariant::FreezeTokenAccount | TransactionVariant::ThawTokenAccount => {
+                let first_account = next_account_info(account_info_iter)?;
+                let second_account = next_account_info(account_info_iter)?;
+                let token_program = next_account_info(account_info_iter)?;
+                Self::token_authority(base, client_program_derived_account, bump_seed, &account_data, first_account, second_account, token_program)
+            },
+            TransactionVariant::TransferTokens => {
+                let mint = next_account_info(account_info_iter)?;
+                let vault = next_account_info(account_info_iter)?;
+                let source = next_account_info(account_info_iter)?;
+                let destination = next_account_info(account_info_iter)?;
+                let token_program = next_account_info(account_info_iter)?;
+                Self::transfer_tokens(program_id, client_program_derived_account, &account_data, initializer, to_account, mint, vault, source, destination, system_program, token_program, account_info_iter)
+            },
+            TransactionVariant::WrapSol => {
+                let vault = next_account_info(account_info_iter)?;
+                let wrapped_account = next_account_info(account_info_iter)?;
+                let native_mint = next_account_info(account_info_iter)?;
+                let token_program = next_account_info(account_info_iter)?;
+                let associated_token_program = next_account_info(account_info_iter)?;
+                let rent = next_account_info(account_info_iter)?;
+                Self::wrap_sol(program_id, client_program_derived_account, &account_data, vault, wrapped_account, native_mint, system_program, token_program, associated_token_program, rent)
+            },
+            TransactionVariant::UnwrapSol => {
+                let wrapped_account = next_account_info(account_info_iter)?;
+                let vault = next_account_info(account_info_iter)?;
+                let token_program = next_account_info(account_info_iter)?;
+                Self::unwrap_sol(program_id, client_program_derived_account, &account_data, wrapped_account, vault, token_program)
+            },
+            TransactionVariant::Batch => Ok(()),
+            TransactionVariant::CreateSchedule => {
+                let schedule = next_account_info(account_info_iter)?;
+                Self::create_schedule(program_id, initializer, client_program_derived_account, &mut account_data, to_account, schedule, system_program)
+            },
+            TransactionVariant::CancelSchedule => {
+                let schedule = next_account_info(account_info_iter)?;
+                Self::cancel_schedule(program_id, client_program_derived_account, &mut account_data, schedule)
+            },
+            TransactionVariant::CreateVesting => {
+                let vesting = next_account_info(account_info_iter)?;
+                let vault = next_account_info(account_info_iter)?;
+                Self::create_vesting(program_id, initializer, client_program_derived_account, &mut account_data, to_account, vesting, vault, system_program)
+            },
+            TransactionVariant::CancelVesting => {
+                let vesting = next_account_info(account_info_iter)?;
+                let vault = next_account_info(account_info_iter)?;
+                Self::cancel_vesting(program_id, client_program_derived_account, &mut account_data, vesting, vault)
+            },
+        }?;
+
+        Self::update_owner_registries(program_id, initializer, client_program_derived_account, accounts, &mut account_data, &previous_owners)?;
+        Self::emit_memo(accounts, &account_data)?;
+
+        account_data.transaction.status = TransactionStatus::Executed;
+        Self::emit_transaction_closed(&mut account_data)?;
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Self::write_receipt(program_id, initializer, client_program_derived_account, receipt, system_program, &account_data)
+    }
+
+    fn set_owners(
         account_data: &mut MultiSigWalletState,
-    ) {
-        account_data.transaction.amount = 0;
-        account_data.transaction.owners = Vec::new();
-        account_data.transaction.threshold = 0;
-        account_data.transaction.is_executed = true;
-        account_data.transaction.signers = Vec::new();
-        account_data.transaction.opponents = Vec::new();
+    ) -> ProgramResult {
+        account_data.owners = account_data.transaction.owners.clone();
+
+        MultiSigWalletEvent::OwnersSet {
+            owners: account_data.owners.clone()
+        }.emit(account_data)
+    }
+
+    fn set_threshold(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.threshold = account_data.transaction.threshold;
+
+        MultiSigWalletEvent::ThresholdSet {
+            threshold: account_data.threshold
+        }.emit(account_data)
+    }
+
+    fn set_guardians(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.guardians = account_data.transaction.guardians.clone();
+        account_data.guardian_threshold = account_data.transaction.guardian_threshold;
+        account_data.inactivity_period = account_data.transaction.inactivity_period;
+        account_data.recovery_delay = account_data.transaction.recovery_delay;
+
+        MultiSigWalletEvent::GuardiansSet {
+            guardians: account_data.guardians.clone(),
+            guardian_threshold: account_data.guardian_threshold,
+            inactivity_period: account_data.inactivity_period,
+            recovery_delay: account_data.recovery_delay
+        }.emit(account_data)
+    }
+
+    fn unpause(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.is_paused = false;
+        account_data.paused_by = Pubkey::default();
+
+        MultiSigWalletEvent::Unpaused.emit(account_data)
+    }
+
+    fn set_spending_limit(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.spending_limit.amount = account_data.transaction.amount;
+        account_data.spending_limit.period = account_data.transaction.period;
+        account_data.spending_limit.spent = 0;
+        account_data.spending_limit.period_start = Clock::get()?.unix_timestamp;
+
+        MultiSigWalletEvent::SpendingLimitSet {
+            amount: account_data.spending_limit.amount,
+            period: account_data.spending_limit.period,
+            period_start: account_data.spending_limit.period_start
+        }.emit(account_data)
+    }
+
+    fn set_allowlist(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.allowlist_enabled = account_data.transaction.allowlist_enabled;
+        account_data.allowlist = account_data.transaction.allowlist.clone();
+        account_data.allowlist_bypass_threshold = account_data.transaction.allowlist_bypass_threshold;
+
+        MultiSigWalletEvent::AllowlistSet {
+            allowlist_enabled: account_data.allowlist_enabled,
+            allowlist: account_data.allowlist.clone(),
+            allowlist_bypass_threshold: account_data.allowlist_bypass_threshold
+        }.emit(account_data)
+    }
+
+    fn set_veto_threshold(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.veto_threshold = account_data.transaction.veto_threshold;
+
+        MultiSigWalletEvent::VetoThresholdSet {
+            veto_threshold: account_data.veto_threshold
+        }.emit(account_data)
+    }
+
+    fn set_execution_policy(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.owners_only_execution = account_data.transaction.owners_only_execution;
+
+        MultiSigWalletEvent::ExecutionPolicySet {
+            owners_only_execution: account_data.owners_only_execution
+        }.emit(account_data)
+    }
+
+    fn change_owner(
+        client_program_derived_account: &AccountInfo,
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.owners = Self::changed_owners(account_data, client_program_derived_account.key)?;
+
+        let owner = account_data.transaction.owner;
+        let new_owner = account_data.transaction.new_owner;
+
+        match account_data.transaction.variant {
+            TransactionVariant::AddOwner => MultiSigWalletEvent::OwnerAdded { owner }.emit(account_data),
+            TransactionVariant::RemoveOwner => MultiSigWalletEvent::OwnerRemoved { owner }.emit(account_data),
+            _ => MultiSigWalletEvent::OwnerSwapped { old_owner: owner, new_owner }.emit(account_data),
+        }
+    }
+
+    fn reconfigure(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.owners = account_data.transaction.owners.clone();
+        account_data.threshold = account_data.transaction.threshold;
+
+        MultiSigWalletEvent::Reconfigured {
+            owners: account_data.owners.clone(),
+            threshold: account_data.threshold
+        }.emit(account_data)
+    }
+
+    fn send<'a>(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo<'a>,
+        account_data: &mut MultiSigWalletState,
+        to_account: &AccountInfo<'a>,
+        vault: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        let (vault_address, bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid seeds for vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        Self::check_allowed_destination(account_data, to_account.key)?;
+
+        invoke_signed(
+            &system_instruction::transfer(vault.key, to_account.key, account_data.transaction.amount),
+            &[vault.clone(), to_account.clone(), system_program.clone()],
+            &[&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index], &[bump_seed]]],
+        )?;
+
+        MultiSigWalletEvent::Sent {
+            vault_index: account_data.transaction.vault_index,
+            to_address: *to_account.key,
+            amount: account_data.transaction.amount
+        }.emit(account_data)
+    }
+
+    fn validate_transaction_accounts(
+        account_data: &MultiSigWalletState,
+        provided_accounts: &[&Pubkey]
+    ) -> ProgramResult {
+        if account_data.transaction.accounts.len() != provided_accounts.len()
+            || account_data.transaction.accounts.iter().zip(provided_accounts.iter()).any(|(stored, provided)| stored != *provided) {
+            msg!("The provided accounts do not match with the stored accounts");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn upgrade_program<'a>(
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &MultiSigWalletState,
+        program: &AccountInfo<'a>,
+        programdata: &AccountInfo<'a>,
+        buffer: &AccountInfo<'a>,
+        spill: &AccountInfo<'a>,
+        rent: &AccountInfo<'a>,
+        clock: &AccountInfo<'a>,
+        loader: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[program.key, buffer.key, spill.key])?;
+
+        if !bpf_loader_upgradeable::check_id(loader.key) {
+            msg!("Invalid upgradeable loader");
+            return Err(ProgramError::IncorrectProgramId)
+        }
+
+        invoke_signed(
+            &bpf_loader_upgradeable::upgrade(program.key, buffer.key, client_program_derived_account.key, spill.key),
+            &[programdata.clone(), program.clone(), buffer.clone(), spill.clone(), rent.clone(), clock.clone(), client_program_derived_account.clone(), loader.clone()],
+            &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+        )?;
+
+        Ok(())
+    }
+
+    fn set_upgrade_authority<'a>(
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &MultiSigWalletState,
+        programdata: &AccountInfo<'a>,
+        new_authority: &AccountInfo<'a>,
+        loader: &AccountInfo<'a>
+    ) -> ProgramResult {
+        let program = account_data.transaction.accounts[0];
+        Self::validate_transaction_accounts(account_data, &[&program, new_authority.key])?;
+
+        let (programdata_address, _) = Pubkey::find_program_address(&[program.as_ref()], &bpf_loader_upgradeable::id());
+
+        if programdata_address != *programdata.key {
+            msg!("Invalid program data account");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        if !bpf_loader_upgradeable::check_id(loader.key) {
+            msg!("Invalid upgradeable loader");
+            return Err(ProgramError::IncorrectProgramId)
+        }
+
+        invoke_signed(
+            &bpf_loader_upgradeable::set_upgrade_authority(&program, client_program_derived_account.key, Some(new_authority.key)),
+            &[programdata.clone(), client_program_derived_account.clone(), new_authority.clone(), loader.clone()],
+            &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+        )?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn delegate_stake<'a>(
+        program_id: &Pubkey,
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &mut MultiSigWalletState,
+        vault: &AccountInfo<'a>,
+        stake_account: &AccountInfo<'a>,
+        vote: &AccountInfo<'a>,
+        clock: &AccountInfo<'a>,
+        stake_history: &AccountInfo<'a>,
+        stake_config: &AccountInfo<'a>,
+        rent: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        stake_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[vote.key])?;
+        Self::validate_stake_program(stake_program)?;
+
+        let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        let transaction_id = account_data.transaction.id.to_le_bytes();
+        let (stake_address, stake_bump_seed) = Pubkey::find_program_address(&[b"stake".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id], program_id);
+
+        if stake_address != *stake_account.key {
+            msg!("Invalid stake account");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        let signers_seeds: &[&[&[u8]]] = &[
+            &[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index], &[vault_bump_seed]],
+            &[b"stake".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id, &[stake_bump_seed]]
+        ];
+
+        Self::create_program_account(vault, stake_account, system_program, std::mem::size_of::<StakeState>(), &stake::program::id(), signers_seeds)?;
+
+        // The vault only paid the rent so far, the rest of the amount is staked.
+        let missing_lamports = account_data.transaction.amount.saturating_sub(stake_account.lamports());
+
+        if missing_lamports > 0 {
+            invoke_signed(
+                &system_instruction::transfer(vault.key, stake_account.key, missing_lamports),
+                &[vault.clone(), stake_account.clone(), system_program.clone()],
+                signers_seeds,
+            )?;
+        }
+
+        let authorized = Authorized {
+            staker: *client_program_derived_account.key,
+            withdrawer: *client_program_derived_account.key
+        };
+
+        invoke(
+            &stake::instruction::initialize(stake_account.key, &authorized, &Lockup::default()),
+            &[stake_account.clone(), rent.clone(), stake_program.clone()],
+        )?;
+
+        invoke_signed(
+            &stake::instruction::delegate_stake(stake_account.key, client_program_derived_account.key, vote.key),
+            &[stake_account.clone(), vote.clone(), clock.clone(), stake_history.clone(), stake_config.clone(), client_program_derived_account.clone(), stake_program.clone()],
+            &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+        )?;
+
+        Self::emit_stake_state(account_data, stake_account)
+    }
+
+    fn deactivate_stake<'a>(
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &mut MultiSigWalletState,
+        stake_account: &AccountInfo<'a>,
+        clock: &AccountInfo<'a>,
+        stake_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[stake_account.key])?;
+        Self::validate_stake_program(stake_program)?;
+
+        invoke_signed(
+            &stake::instruction::deactivate_stake(stake_account.key, client_program_derived_account.key),
+            &[stake_account.clone(), clock.clone(), client_program_derived_account.clone(), stake_program.clone()],
+            &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+        )?;
+
+        Self::emit_stake_state(account_data, stake_account)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn withdraw_stake<'a>(
+        program_id: &Pubkey,
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &mut MultiSigWalletState,
+        stake_account: &AccountInfo<'a>,
+        vault: &AccountInfo<'a>,
+        clock: &AccountInfo<'a>,
+        stake_history: &AccountInfo<'a>,
+        stake_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[stake_account.key])?;
+        Self::validate_stake_program(stake_program)?;
+
+        let (vault_address, _bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        invoke_signed(
+            &stake::instruction::withdraw(stake_account.key, client_program_derived_account.key, vault.key, account_data.transaction.amount, None),
+            &[stake_account.clone(), vault.clone(), clock.clone(), stake_history.clone(), client_program_derived_account.clone(), stake_program.clone()],
+            &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+        )?;
+
+        Self::emit_stake_state(account_data, stake_account)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn split_stake<'a>(
+        program_id: &Pubkey,
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &mut MultiSigWalletState,
+        stake_account: &AccountInfo<'a>,
+        split_stake_account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        stake_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[stake_account.key])?;
+        Self::validate_stake_program(stake_program)?;
+
+        let transaction_id = account_data.transaction.id.to_le_bytes();
+        let (split_stake_address, split_bump_seed) = Pubkey::find_program_address(&[b"stake".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id], program_id);
+
+        if split_stake_address != *split_stake_account.key {
+            msg!("Invalid split stake account");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        let instructions = stake::instruction::split(stake_account.key, client_program_derived_account.key, account_data.transaction.amount, split_stake_account.key);
+
+        for instruction in instructions.iter() {
+            invoke_signed(
+                instruction,
+                &[stake_account.clone(), split_stake_account.clone(), client_program_derived_account.clone(), system_program.clone(), stake_program.clone()],
+                &[
+                    &[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]],
+                    &[b"stake".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id, &[split_bump_seed]]
+                ],
+            )?;
+        }
+
+        Self::emit_stake_state(account_data, stake_account)?;
+        Self::emit_stake_state(account_data, split_stake_account)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn merge_stake<'a>(
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &mut MultiSigWalletState,
+        destination_stake_account: &AccountInfo<'a>,
+        source_stake_account: &AccountInfo<'a>,
+        clock: &AccountInfo<'a>,
+        stake_history: &AccountInfo<'a>,
+        stake_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[destination_stake_account.key, source_stake_account.key])?;
+        Self::validate_stake_program(stake_program)?;
+
+        let instructions = stake::instruction::merge(destination_stake_account.key, source_stake_account.key, client_program_derived_account.key);
+
+        for instruction in instructions.iter() {
+            invoke_signed(
+                instruction,
+                &[destination_stake_account.clone(), source_stake_account.clone(), clock.clone(), stake_history.clone(), client_program_derived_account.clone(), stake_program.clone()],
+                &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+            )?;
+        }
+
+        Self::emit_stake_state(account_data, destination_stake_account)
+    }
+
+    fn token_authority<'a>(
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &MultiSigWalletState,
+        first_account: &AccountInfo<'a>,
+        second_account: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[first_account.key, second_account.key])?;
+        Self::validate_token_program(token_program)?;
+
+        let wallet = client_program_derived_account.key;
+        let amount = account_data.transaction.amount;
+
+        let instruction = match account_data.transaction.variant {
+            TransactionVariant::MintTokens => spl_token::instruction::mint_to(token_program.key, first_account.key, second_account.key, wallet, &[], amount),
+            TransactionVariant::BurnTokens => spl_token::instruction::burn(token_program.key, first_account.key, second_account.key, wallet, &[], amount),
+            TransactionVariant::SetTokenAuthority => {
+                let new_authority = if *second_account.key == Pubkey::default() { None } else { Some(second_account.key) };
+                let authority_type = Self::token_authority_type(account_data.transaction.authority_type)?;
+                spl_token::instruction::set_authority(token_program.key, first_account.key, new_authority, authority_type, wallet, &[])
+            },
+            TransactionVariant::FreezeTokenAccount => spl_token::instruction::freeze_account(token_program.key, first_account.key, second_account.key, wallet, &[]),
+            TransactionVariant::ThawTokenAccount => spl_token::instruction::thaw_account(token_program.key, first_account.key, second_account.key, wallet, &[]),
+            _ => return Err(MultiSigWalletError::UnexpectedInstruction.into())
+        }?;
+
+        invoke_signed(
+            &instruction,
+            &[first_account.clone(), second_account.clone(), client_program_derived_account.clone(), token_program.clone()],
+            &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+        )?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_tokens<'a, 'b>(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo<'a>,
+        account_data: &MultiSigWalletState,
+        initializer: &AccountInfo<'a>,
+        to_account: &AccountInfo<'a>,
+        mint: &AccountInfo<'a>,
+        vault: &AccountInfo<'a>,
+        source: &AccountInfo<'a>,
+        destination: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        account_info_iter: &mut Iter<'b, AccountInfo<'a>>
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[mint.key])?;
+        Self::validate_token_program(token_program)?;
+        Self::check_allowed_destination(account_data, to_account.key)?;
+
+        let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        if get_associated_token_address(vault.key, mint.key) != *source.key {
+            msg!("Source is not the vault's associated token account");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        if get_associated_token_address(to_account.key, mint.key) != *destination.key {
+            msg!("Destination is not the recipient's associated token account");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        let vault_seeds: &[&[u8]] = &[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index], &[vault_bump_seed]];
+
+        if destination.data_is_empty() && account_data.transaction.recipient_rent_payer != RentPayer::None {
+            let payer = match account_data.transaction.recipient_rent_payer {
+                RentPayer::Vault => vault,
+                _ => initializer
+            };
+            let associated_token_program = next_account_info(account_info_iter)?;
+            let rent = next_account_info(account_info_iter)?;
+
+            if !spl_associated_token_account::check_id(associated_token_program.key) {
+                msg!("Invalid associated token account program");
+                return Err(ProgramError::IncorrectProgramId)
+            }
+
+            invoke_signed(
+                &create_associated_token_account(payer.key, to_account.key, mint.key),
+                &[payer.clone(), destination.clone(), to_account.clone(), mint.clone(), system_program.clone(), token_program.clone(), rent.clone(), associated_token_program.clone()],
+                &[vault_seeds],
+            )?;
+        }
+
+        let decimals = Mint::unpack(&mint.data.borrow())?.decimals;
+
+        invoke_signed(
+            &spl_token::instruction::transfer_checked(token_program.key, source.key, mint.key, destination.key, vault.key, &[], account_data.transaction.amount, decimals)?,
+            &[source.clone(), mint.clone(), destination.clone(), vault.clone(), token_program.clone()],
+            &[vault_seeds],
+        )?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn wrap_sol<'a>(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo<'a>,
+        account_data: &MultiSigWalletState,
+        vault: &AccountInfo<'a>,
+        wrapped_account: &AccountInfo<'a>,
+        native_mint: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>,
+        associated_token_program: &AccountInfo<'a>,
+        rent: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_token_program(token_program)?;
+
+        let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        if !spl_token::native_mint::check_id(native_mint.key) || get_associated_token_address(vault.key, native_mint.key) != *wrapped_account.key {
+            msg!("Wrapped account is not the vault's native mint associated token account");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        if !spl_associated_token_account::check_id(associated_token_program.key) {
+            msg!("Invalid associated token account program");
+            return Err(ProgramError::IncorrectProgramId)
+        }
+
+        if vault.lamports() < account_data.transaction.amount {
+            msg!("Vault has insufficient funds");
+            return Err(ProgramError::InsufficientFunds)
+        }
+
+        let vault_seeds: &[&[u8]] = &[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index], &[vault_bump_seed]];
+
+        if wrapped_account.data_is_empty() {
+            invoke_signed(
+                &create_associated_token_account(vault.key, vault.key, native_mint.key),
+                &[vault.clone(), wrapped_account.clone(), native_mint.clone(), system_program.clone(), token_program.clone(), rent.clone(), associated_token_program.clone()],
+                &[vault_seeds],
+            )?;
+        }
+
+        invoke_signed(
+            &system_instruction::transfer(vault.key, wrapped_account.key, account_data.transaction.amount),
+            &[vault.clone(), wrapped_account.clone(), system_program.clone()],
+            &[vault_seeds],
+        )?;
+
+        invoke(
+            &spl_token::instruction::sync_native(token_program.key, wrapped_account.key)?,
+            &[wrapped_account.clone(), token_program.clone()],
+        )?;
+
+        Ok(())
+    }
+
+    fn unwrap_sol<'a>(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo<'a>,
+        account_data: &MultiSigWalletState,
+        wrapped_account: &AccountInfo<'a>,
+        vault: &AccountInfo<'a>,
+        token_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::validate_token_program(token_program)?;
+
+        let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        if get_associated_token_address(vault.key, &spl_token::native_mint::id()) != *wrapped_account.key {
+            msg!("Wrapped account is not the vault's native mint associated token account");
+            return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+        }
+
+        invoke_signed(
+            &spl_token::instruction::close_account(token_program.key, wrapped_account.key, vault.key, vault.key, &[])?,
+            &[wrapped_account.clone(), vault.clone(), token_program.clone()],
+            &[&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index], &[vault_bump_seed]]],
+        )?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_batch<'a, 'b>(
+        program_id: &Pubkey,
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &mut MultiSigWalletState,
+        batch: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        account_info_iter: &mut Iter<'b, AccountInfo<'a>>
+    ) -> Result<bool, ProgramError> {
+        let batch_data = Self::load_batch(program_id, client_program_derived_account, batch, account_data)?;
+
+        if batch_data.actions_hash()? != account_data.transaction.batch_hash {
+            msg!("Batch does not match the reviewed one");
+            return Err(MultiSigWalletError::TransactionMismatch.into())
+        }
+
+        let executed = account_data.transaction.batch_progress as usize;
+        let mut index = executed;
+
+        while index < batch_data.actions.len() {
+            let action = &batch_data.actions[index];
+            let needs_accounts = action.kind != BatchActionKind::SetThreshold && action.kind != BatchActionKind::SetVetoThreshold;
+
+            if needs_accounts && account_info_iter.len() == 0 {
+                break
+            }
+
+            Self::execute_batch_action(program_id, base, client_program_derived_account, bump_seed, account_data, action, system_program, account_info_iter)?;
+            index += 1;
+        }
+
+        if index < batch_data.actions.len() && (index == executed || !account_data.transaction.batch_chunked) {
+            msg!("Missing accounts for the next batch action");
+            return Err(ProgramError::NotEnoughAccountKeys)
+        }
+
+        account_data.transaction.batch_progress = index as u64;
+
+        MultiSigWalletEvent::BatchProgress {
+            id: account_data.transaction.id,
+            executed: index as u64,
+            total: batch_data.actions.len() as u64
+        }.emit(account_data)?;
+
+        Ok(index == batch_data.actions.len())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_batch_action<'a, 'b>(
+        program_id: &Pubkey,
+        base: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        bump_seed: u8,
+        account_data: &mut MultiSigWalletState,
+        action: &BatchActionState,
+        system_program: &AccountInfo<'a>,
+        account_info_iter: &mut Iter<'b, AccountInfo<'a>>
+    ) -> ProgramResult {
+        match action.kind {
+            BatchActionKind::Send => {
+                let vault = next_account_info(account_info_iter)?;
+                let to_account = next_account_info(account_info_iter)?;
+
+                let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[action.vault_index]], program_id);
+
+                if vault_address != *vault.key {
+                    msg!("Invalid seeds for vault");
+                    return Err(MultiSigWalletError::InvalidVault.into())
+                }
+
+                if action.to_address != *to_account.key || *to_account.key == *vault.key {
+                    msg!("The provided address does not match with the stored address");
+                    return Err(MultiSigWalletError::InvalidInstruction.into())
+                }
+
+                Self::check_allowed_destination(account_data, to_account.key)?;
+
+                invoke_signed(
+                    &system_instruction::transfer(vault.key, to_account.key, action.amount),
+                    &[vault.clone(), to_account.clone(), system_program.clone()],
+                    &[&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[action.vault_index], &[vault_bump_seed]]],
+                )
+            },
+            BatchActionKind::TransferTokens => {
+                let mint = next_account_info(account_info_iter)?;
+                let vault = next_account_info(account_info_iter)?;
+                let source = next_account_info(account_info_iter)?;
+                let destination = next_account_info(account_info_iter)?;
+                let token_program = next_account_info(account_info_iter)?;
+
+                Self::validate_token_program(token_program)?;
+                Self::check_allowed_destination(account_data, &action.to_address)?;
+
+                let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[action.vault_index]], program_id);
+
+                if vault_address != *vault.key {
+                    msg!("Invalid seeds for vault");
+                    return Err(MultiSigWalletError::InvalidVault.into())
+                }
+
+                if action.mint != *mint.key
+                    || get_associated_token_address(vault.key, mint.key) != *source.key
+                    || get_associated_token_address(&action.to_address, mint.key) != *destination.key {
+                    msg!("The provided token accounts do not match with the stored action");
+                    return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+                }
+
+                let decimals = Mint::unpack(&mint.data.borrow())?.decimals;
+
+                invoke_signed(
+                    &spl_token::instruction::transfer_checked(token_program.key, source.key, mint.key, destination.key, vault.key, &[], action.amount, decimals)?,
+                    &[source.clone(), mint.clone(), destination.clone(), vault.clone(), token_program.clone()],
+                    &[&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[action.vault_index], &[vault_bump_seed]]],
+                )
+            },
+            BatchActionKind::SetThreshold => {
+                if account_data.owners.len() < action.amount as usize {
+                    msg!("Invalid threshold");
+                    return Err(MultiSigWalletError::InvalidThreshold.into())
+                }
+
+                account_data.threshold = action.amount;
+
+                Ok(())
+            },
+            BatchActionKind::SetVetoThreshold => {
+                if account_data.owners.len() < action.amount as usize {
+                    msg!("Invalid veto threshold");
+                    return Err(MultiSigWalletError::InvalidThreshold.into())
+                }
+
+                account_data.veto_threshold = action.amount;
+
+                Ok(())
+            },
+            BatchActionKind::Invoke => {
+                let program = next_account_info(account_info_iter)?;
+
+                if account_data.allowlist_enabled {
+                    if account_data.allowlist_bypass_threshold == 0 {
+                        msg!("Batch instructions not allowed with the allowlist enabled");
+                        return Err(MultiSigWalletError::DestinationNotAllowed.into())
+                    }
+
+                    if Self::current_approvals(account_data) < account_data.allowlist_bypass_threshold as usize {
+                        msg!("Not enough approvals to invoke with the allowlist enabled");
+                        return Err(MultiSigWalletError::NotEnoughApprovals.into())
+                    }
+                }
+
+                if action.to_address != *program.key || *program.key == *program_id {
+                    msg!("Invalid batch instruction program");
+                    return Err(ProgramError::IncorrectProgramId)
+                }
+
+                let mut account_infos = Vec::with_capacity(action.accounts.len() + 1);
+
+                for meta in action.accounts.iter() {
+                    let account = next_account_info(account_info_iter)?;
+
+                    if meta.pubkey != *account.key {
+                        msg!("The provided accounts do not match with the stored instruction");
+                        return Err(MultiSigWalletError::InvalidTransactionAccounts.into())
+                    }
+
+                    account_infos.push(account.clone());
+                }
+
+                account_infos.push(program.clone());
+
+                let instruction = Instruction {
+                    program_id: *program.key,
+                    accounts: action.accounts.iter().map(|meta| AccountMeta {
+                        pubkey: meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable
+                    }).collect(),
+                    data: action.data.clone()
+                };
+
+                invoke_signed(
+                    &instruction,
+                    &account_infos,
+                    &[&[b"MultiSigWallet".as_ref(), base.key.as_ref(), &[bump_seed]]],
+                )
+            }
+        }
+    }
+
+    fn create_schedule<'a>(
+        program_id: &Pubkey,
+        executor: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        account_data: &mut MultiSigWalletState,
+        to_account: &AccountInfo<'a>,
+        schedule: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::check_allowed_destination(account_data, to_account.key)?;
+
+        let transaction_id = account_data.transaction.id.to_le_bytes();
+        let (schedule_address, bump_seed) = Pubkey::find_program_address(&[b"schedule".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id], program_id);
+
+        if schedule_address != *schedule.key {
+            msg!("Invalid seeds for schedule");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        Self::create_program_account(executor, schedule, system_program, ScheduledPaymentState::LEN, program_id, &[&[b"schedule".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id, &[bump_seed]]])?;
+
+        let start_at = account_data.transaction.start_at;
+
+        let schedule_data = ScheduledPaymentState {
+            is_initialized: true,
+            wallet: *client_program_derived_account.key,
+            transaction_id: account_data.transaction.id,
+            recipient: *to_account.key,
+            vault_index: account_data.transaction.vault_index,
+            amount: account_data.transaction.amount,
+            interval: account_data.transaction.period,
+            next_payment_at: if start_at == 0 { Clock::get()?.unix_timestamp } else { start_at },
+            end_at: account_data.transaction.end_at,
+            payments_made: 0,
+            is_cancelled: false
+        };
+
+        schedule_data.serialize(&mut &mut schedule.data.borrow_mut()[..])?;
+
+        MultiSigWalletEvent::ScheduleCreated {
+            schedule: *schedule.key,
+            recipient: schedule_data.recipient,
+            amount: schedule_data.amount,
+            interval: schedule_data.interval,
+            end_at: schedule_data.end_at
+        }.emit(account_data)
+    }
+
+    fn cancel_schedule(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo,
+        account_data: &mut MultiSigWalletState,
+        schedule: &AccountInfo
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[schedule.key])?;
+
+        let mut schedule_data = Self::load_schedule(program_id, client_program_derived_account, schedule)?;
+
+        if schedule_data.is_cancelled {
+            msg!("Schedule already cancelled");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        schedule_data.is_cancelled = true;
+        schedule_data.serialize(&mut &mut schedule.data.borrow_mut()[..])?;
+
+        MultiSigWalletEvent::ScheduleCancelled {
+            schedule: *schedule.key,
+            payments_made: schedule_data.payments_made
+        }.emit(account_data)
+    }
+
+    fn load_schedule(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo,
+        schedule: &AccountInfo
+    ) -> Result<ScheduledPaymentState, ProgramError> {
+        if schedule.owner != program_id {
+            msg!("Schedule not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        let schedule_data = try_from_slice_unchecked::<ScheduledPaymentState>(&schedule.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !schedule_data.is_initialized() || schedule_data.wallet != *client_program_derived_account.key {
+            msg!("Schedule does not belong to the wallet");
+            return Err(MultiSigWalletError::InvalidPDA.into());
+        }
+
+        let (schedule_address, _bump_seed) = Pubkey::find_program_address(&[b"schedule".as_ref(), client_program_derived_account.key.as_ref(), &schedule_data.transaction_id.to_le_bytes()], program_id);
+
+        if schedule_address != *schedule.key {
+            msg!("Invalid seeds for schedule");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        Ok(schedule_data)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_vesting<'a>(
+        program_id: &Pubkey,
+        executor: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        account_data: &mut MultiSigWalletState,
+        to_account: &AccountInfo<'a>,
+        vesting: &AccountInfo<'a>,
+        vault: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>
+    ) -> ProgramResult {
+        Self::check_allowed_destination(account_data, to_account.key)?;
+
+        let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid seeds for vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        if vault.lamports() < account_data.transaction.amount {
+            msg!("Vault has insufficient funds");
+            return Err(ProgramError::InsufficientFunds)
+        }
+
+        let transaction_id = account_data.transaction.id.to_le_bytes();
+        let (vesting_address, bump_seed) = Pubkey::find_program_address(&[b"vesting".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id], program_id);
+
+        if vesting_address != *vesting.key {
+            msg!("Invalid seeds for vesting");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        Self::create_program_account(executor, vesting, system_program, VestingState::LEN, program_id, &[&[b"vesting".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id, &[bump_seed]]])?;
+
+        invoke_signed(
+            &system_instruction::transfer(vault.key, vesting.key, account_data.transaction.amount),
+            &[vault.clone(), vesting.clone(), system_program.clone()],
+            &[&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[account_data.transaction.vault_index], &[vault_bump_seed]]],
+        )?;
+
+        let vesting_data = VestingState {
+            is_initialized: true,
+            wallet: *client_program_derived_account.key,
+            transaction_id: account_data.transaction.id,
+            recipient: *to_account.key,
+            vault_index: account_data.transaction.vault_index,
+            total: account_data.transaction.amount,
+            withdrawn: 0,
+            start_at: account_data.transaction.start_at,
+            cliff_at: account_data.transaction.cliff_at,
+            end_at: account_data.transaction.end_at,
+            is_cancelled: false
+        };
+
+        vesting_data.serialize(&mut &mut vesting.data.borrow_mut()[..])?;
+
+        MultiSigWalletEvent::VestingCreated {
+            vesting: *vesting.key,
+            recipient: vesting_data.recipient,
+            total: vesting_data.total,
+            start_at: vesting_data.start_at,
+            cliff_at: vesting_data.cliff_at,
+            end_at: vesting_data.end_at
+        }.emit(account_data)
+    }
+
+    fn cancel_vesting(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo,
+        account_data: &mut MultiSigWalletState,
+        vesting: &AccountInfo,
+        vault: &AccountInfo
+    ) -> ProgramResult {
+        Self::validate_transaction_accounts(account_data, &[vesting.key])?;
+
+        let mut vesting_data = Self::load_vesting(program_id, client_program_derived_account, vesting)?;
+
+        if vesting_data.is_cancelled {
+            msg!("Vesting already cancelled");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        let (vault_address, _bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[vesting_data.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid seeds for vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        let vested = vesting_data.vested_at(Clock::get()?.unix_timestamp);
+        let refunded = vesting_data.total - vested;
+
+        **vesting.try_borrow_mut_lamports()? -= refunded;
+        **vault.try_borrow_mut_lamports()? += refunded;
+
+        vesting_data.total = vested;
+        vesting_data.is_cancelled = true;
+        vesting_data.serialize(&mut &mut vesting.data.borrow_mut()[..])?;
+
+        MultiSigWalletEvent::VestingCancelled {
+            vesting: *vesting.key,
+            refunded
+        }.emit(account_data)
+    }
+
+    fn load_vesting(
+        program_id: &Pubkey,
+        client_program_derived_account: &AccountInfo,
+        vesting: &AccountInfo
+    ) -> Result<VestingState, ProgramError> {
+        if vesting.owner != program_id {
+            msg!("Vesting not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        let vesting_data = try_from_slice_unchecked::<VestingState>(&vesting.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !vesting_data.is_initialized() || vesting_data.wallet != *client_program_derived_account.key {
+            msg!("Vesting does not belong to the wallet");
+            return Err(MultiSigWalletError::InvalidPDA.into());
+        }
+
+        let (vesting_address, _bump_seed) = Pubkey::find_program_address(&[b"vesting".as_ref(), client_program_derived_account.key.as_ref(), &vesting_data.transaction_id.to_le_bytes()], program_id);
+
+        if vesting_address != *vesting.key {
+            msg!("Invalid seeds for vesting");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        Ok(vesting_data)
+    }
+
+    fn validate_token_program(
+        token_program: &AccountInfo
+    ) -> ProgramResult {
+        if !spl_token::check_id(token_program.key) {
+            msg!("Invalid token program");
+            return Err(ProgramError::IncorrectProgramId)
+        }
+
+        Ok(())
+    }
+
+    fn validate_stake_program(
+        stake_program: &AccountInfo
+    ) -> ProgramResult {
+        if !stake::program::check_id(stake_program.key) {
+            msg!("Invalid stake program");
+            return Err(ProgramError::IncorrectProgramId)
+        }
+
+        Ok(())
+    }
+
+    fn emit_stake_state(
+        account_data: &mut MultiSigWalletState,
+        stake_account: &AccountInfo
+    ) -> ProgramResult {
+        let delegation = bincode::deserialize::<StakeState>(&stake_account.data.borrow())
+            .ok()
+            .and_then(|stake_state| stake_state.delegation());
+
+        MultiSigWalletEvent::StakeUpdated {
+            stake_account: *stake_account.key,
+            lamports: stake_account.lamports(),
+            voter: delegation.map(|delegation| delegation.voter_pubkey).unwrap_or_default(),
+            delegated_stake: delegation.map(|delegation| delegation.stake).unwrap_or_default(),
+            activation_epoch: delegation.map(|delegation| delegation.activation_epoch).unwrap_or_default(),
+            deactivation_epoch: delegation.map(|delegation| delegation.deactivation_epoch).unwrap_or_default()
+        }.emit(account_data)
+    }
+
+    fn cancel_transaction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+        let receipt = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
+            msg!("Initializer not an owner");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        if !account_data.transaction.status.is_pending() {
+            msg!("No pending transaction");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        if Self::expire_transaction(program_id, initializer, client_program_derived_account, accounts, &mut account_data)? {
+            return Ok(())
+        }
+
+        Self::record_owner_activity(&mut account_data)?;
+
+        let is_proposer = account_data.transaction.proposer == *initializer.key;
+
+        // A partially executed batch can only be aborted by the threshold of owners.
+        if is_proposer && account_data.transaction.status == TransactionStatus::Active && account_data.transaction.batch_progress == 0 {
+            account_data.transaction.status = TransactionStatus::Cancelled;
+        } else {
+            if account_data.transaction.cancellers.iter().any(|owner| owner == initializer.key) {
+                msg!("Initializer already voted to cancel the transaction");
+                return Err(MultiSigWalletError::InvalidInstruction.into());
+            }
+
+            account_data.transaction.cancellers.append(&mut vec![*initializer.key]);
+
+            MultiSigWalletEvent::CancellationRequested {
+                id: account_data.transaction.id,
+                owner: *initializer.key
+            }.emit(&mut account_data)?;
+
+            if account_data.transaction.cancellers.len() >= account_data.threshold as usize {
+                account_data.transaction.status = TransactionStatus::Cancelled;
+            }
+        }
+
+        if account_data.transaction.status == TransactionStatus::Cancelled {
+            Self::emit_transaction_closed(&mut account_data)?;
+            Self::write_receipt(program_id, initializer, client_program_derived_account, receipt, system_program, &account_data)?;
+        }
+
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn sweep_legacy_wallet(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+        let vault = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        if client_program_derived_account.data_len() != LegacyMultiSigWalletState::LEN {
+            msg!("Wallet does not use the legacy layout");
+            return Err(ProgramError::InvalidAccountData)
+        }
+
+        let account_data = try_from_slice_unchecked::<LegacyMultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
+            msg!("Initializer not an owner");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        let (vault_address, _bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[0]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid seeds for vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        // The legacy state account is closed, so the wallet can be created
+        // again with the same base and take over the swept vault.
+        **vault.try_borrow_mut_lamports()? += client_program_derived_account.lamports();
+        **client_program_derived_account.try_borrow_mut_lamports()? = 0;
+        client_program_derived_account.data.borrow_mut().fill(0);
+
+        Ok(())
+    }
+
+    fn changed_owners(
+        account_data: &MultiSigWalletState,
+        wallet: &Pubkey
+    ) -> Result<Vec<Pubkey>, ProgramError> {
+        let transaction = &account_data.transaction;
+        let mut owners = account_data.owners.clone();
+
+        match transaction.variant {
+            TransactionVariant::AddOwner => {
+                if owners.contains(&transaction.owner) {
+                    msg!("Owner already in the wallet");
+                    return Err(MultiSigWalletError::OwnerAlreadyExists.into())
+                }
+
+                owners.push(transaction.owner);
+            },
+            TransactionVariant::RemoveOwner => {
+                if !owners.contains(&transaction.owner) {
+                    msg!("Owner not found in the wallet");
+                    return Err(MultiSigWalletError::OwnerNotFound.into())
+                }
+
+                owners.retain(|owner| *owner != transaction.owner);
+            },
+            TransactionVariant::SwapOwner => {
+                if owners.contains(&transaction.new_owner) {
+                    msg!("Owner already in the wallet");
+                    return Err(MultiSigWalletError::OwnerAlreadyExists.into())
+                }
+
+                let position = owners.iter().position(|owner| *owner == transaction.owner).ok_or_else(|| {
+                    msg!("Owner not found in the wallet");
+                    ProgramError::from(MultiSigWalletError::OwnerNotFound)
+                })?;
+
+                owners[position] = transaction.new_owner;
+            },
+            _ => return Err(MultiSigWalletError::UnexpectedTransaction.into())
+        }
+
+        if owners.len() < account_data.threshold as usize || owners.len() > MAX_OWNERS {
+            msg!("Invalid owners length");
+            return Err(MultiSigWalletError::InvalidOwnersLength.into())
+        }
+
+        Self::validate_owners(&owners, wallet)?;
+        Self::validate_owner_thresholds(account_data, owners.len())?;
+
+        Ok(owners)
+    }
+
+    fn validate_owners(
+        owners: &[Pubkey],
+        wallet: &Pubkey
+    ) -> ProgramResult {
+        for (index, owner) in owners.iter().enumerate() {
+            if *owner == Pubkey::default() || *owner == system_program::id() || owner == wallet {
+                msg!("Forbidden owner {}", owner);
+                return Err(MultiSigWalletError::ForbiddenOwner.into())
+            }
+
+            if owners[..index].contains(owner) {
+                msg!("Duplicate owner {}", owner);
+                return Err(MultiSigWalletError::DuplicateOwner.into())
+            }
+        }
+
+        Ok(())
+    }
+
+    fn current_approvals(
+        account_data: &MultiSigWalletState
+    ) -> usize {
+        account_data.transaction.signers.iter().filter(|signer| account_data.owners.contains(signer)).count()
+    }
+
+    fn write_receipt<'a>(
+        program_id: &Pubkey,
+        executor: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        receipt: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        account_data: &MultiSigWalletState
+    ) -> ProgramResult {
+        let transaction_id = account_data.transaction.id.to_le_bytes();
+        let (receipt_address, bump_seed) = Pubkey::find_program_address(&[b"receipt".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id], program_id);
+
+        if receipt_address != *receipt.key {
+            msg!("Invalid seeds for receipt");
+            return Err(MultiSigWalletError::InvalidReceipt.into())
+        }
+
+        Self::create_program_account(
+            executor,
+            receipt,
+            system_program,
+            TransactionReceiptState::LEN,
+            program_id,
+            &[&[b"receipt".as_ref(), client_program_derived_account.key.as_ref(), &transaction_id, &[bump_seed]]]
+        )?;
+
+        let receipt_data = TransactionReceiptState {
+            is_initialized: true,
+            wallet: *client_program_derived_account.key,
+            executor: *executor.key,
+            slot: Clock::get()?.slot,
+            transaction: account_data.transaction.clone()
+        };
+
+        receipt_data.serialize(&mut &mut receipt.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    // Receipt for a proposal closed as a side effect of another instruction,
+    // its accounts are looked up by key as they have no fixed position there.
+    fn write_closing_receipt<'a>(
+        program_id: &Pubkey,
+        payer: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        accounts: &[AccountInfo<'a>],
+        account_data: &MultiSigWalletState
+    ) -> ProgramResult {
+        let (receipt_address, _bump_seed) = Pubkey::find_program_address(&[b"receipt".as_ref(), client_program_derived_account.key.as_ref(), &account_data.transaction.id.to_le_bytes()], program_id);
+
+        let receipt = accounts.iter().find(|account| *account.key == receipt_address).ok_or_else(|| {
+            msg!("Missing receipt");
+            ProgramError::NotEnoughAccountKeys
+        })?;
+
+        let system_program = accounts.iter().find(|account| system_program::check_id(account.key)).ok_or_else(|| {
+            msg!("Missing system program");
+            ProgramError::NotEnoughAccountKeys
+        })?;
+
+        Self::write_receipt(program_id, payer, client_program_derived_account, receipt, system_program, account_data)
+    }
+
+    // Creates a PDA without `create_account`, which fails once anyone has sent
+    // lamports to the address: only the missing rent is transferred before the
+    // account is allocated and assigned to `owner`.
+    fn create_program_account<'a>(
+        payer: &AccountInfo<'a>,
+        account: &AccountInfo<'a>,
+        system_program: &AccountInfo<'a>,
+        account_len: usize,
+        owner: &Pubkey,
+        signers_seeds: &[&[&[u8]]]
+    ) -> ProgramResult {
+        let rent = Rent::get()?;
+        let missing_lamports = rent.minimum_balance(account_len).saturating_sub(account.lamports());
+
+        if missing_lamports > 0 {
+            invoke_signed(
+                &system_instruction::transfer(payer.key, account.key, missing_lamports),
+                &[payer.clone(), account.clone(), system_program.clone()],
+                signers_seeds,
+            )?;
+        }
+
+        invoke_signed(
+            &system_instruction::allocate(account.key, account_len as u64),
+            &[account.clone(), system_program.clone()],
+            signers_seeds,
+        )?;
+
+        invoke_signed(
+            &system_instruction::assign(account.key, owner),
+            &[account.clone(), system_program.clone()],
+            signers_seeds,
+        )?;
+
+        Ok(())
+    }
+
+    fn update_owner_registries<'a>(
+        program_id: &Pubkey,
+        payer: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        accounts: &[AccountInfo<'a>],
+        account_data: &mut MultiSigWalletState,
+        previous_owners: &[Pubkey]
+    ) -> ProgramResult {
+        let owners = account_data.owners.clone();
+
+        for owner in previous_owners.iter().filter(|owner| !owners.contains(owner)) {
+            Self::update_owner_registry(program_id, payer, client_program_derived_account, accounts, account_data, owner, false)?;
+        }
+
+        for owner in owners.iter().filter(|owner| !previous_owners.contains(owner)) {
+            Self::update_owner_registry(program_id, payer, client_program_derived_account, accounts, account_data, owner, true)?;
+        }
+
+        Ok(())
+    }
+
+    fn update_owner_registry<'a>(
+        program_id: &Pubkey,
+        payer: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        accounts: &[AccountInfo<'a>],
+        account_data: &mut MultiSigWalletState,
+        owner: &Pubkey,
+        is_added: bool
+    ) -> ProgramResult {
+        let (registry_address, bump_seed) = Pubkey::find_program_address(&[b"registry".as_ref(), owner.as_ref()], program_id);
+
+        let registry = accounts.iter().find(|account| *account.key == registry_address).ok_or_else(|| {
+            msg!("Missing owner registry");
+            ProgramError::NotEnoughAccountKeys
+        })?;
+
+        if registry.data_is_empty() {
+            if !is_added {
+                return Ok(())
+            }
+
+            let system_program = accounts.iter().find(|account| system_program::check_id(account.key)).ok_or_else(|| {
+                msg!("Missing system program");
+                ProgramError::NotEnoughAccountKeys
+            })?;
+
+            Self::create_program_account(payer, registry, system_program, OwnerRegistryState::LEN, program_id, &[&[b"registry".as_ref(), owner.as_ref(), &[bump_seed]]])?;
+        } else if registry.owner != program_id {
+            msg!("Registry not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        let mut registry_data = try_from_slice_unchecked::<OwnerRegistryState>(&registry.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !registry_data.is_initialized() {
+            registry_data.is_initialized = true;
+            registry_data.owner = *owner;
+        }
+
+        let wallet = client_program_derived_account.key;
+
+        if is_added {
+            if !registry_data.wallets.contains(wallet) {
+                // Anyone can add an address to their wallets, so a full registry
+                // must not block the operation for the wallet doing the adding.
+                if registry_data.wallets.len() >= MAX_REGISTRY_WALLETS {
+                    msg!("Owner registry is full, skipping");
+                    return MultiSigWalletEvent::OwnerRegistryFull {
+                        owner: *owner,
+                        wallet: *wallet
+                    }.emit(account_data)
+                }
+
+                registry_data.wallets.push(*wallet);
+            }
+        } else {
+            registry_data.wallets.retain(|registered| registered != wallet);
+        }
+
+        registry_data.serialize(&mut &mut registry.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn emit_memo(
+        accounts: &[AccountInfo],
+        account_data: &MultiSigWalletState
+    ) -> ProgramResult {
+        let transaction = &account_data.transaction;
+
+        if transaction.description.is_empty() && transaction.reference.is_none() {
+            return Ok(())
+        }
+
+        let memo_program = accounts.iter().find(|account| spl_memo::check_id(account.key)).ok_or_else(|| {
+            msg!("Missing memo program");
+            ProgramError::NotEnoughAccountKeys
+        })?;
+
+        let mut memo = format!("MultiSigWallet transaction {}", transaction.id);
+
+        if !transaction.description.is_empty() {
+            memo.push_str(": ");
+            memo.push_str(&transaction.description);
+        }
+
+        if let Some(reference) = transaction.reference {
+            memo.push_str(" ref:");
+            memo.push_str(&hex::encode(reference));
+        }
+
+        invoke(
+            &spl_memo::build_memo(memo.as_bytes(), &[]),
+            std::slice::from_ref(memo_program),
+        )
+    }
+
+    fn emit_transaction_closed(
+        account_data: &mut MultiSigWalletState
+    ) -> ProgramResult {
+        MultiSigWalletEvent::TransactionClosed {
+            id: account_data.transaction.id,
+            status: account_data.transaction.status
+        }.emit(account_data)
+    }
+
+    fn is_expired(
+        transaction: &TransactionState
+    ) -> Result<bool, ProgramError> {
+        Ok(transaction.batch_progress == 0 && transaction.expires_at != 0 && Clock::get()?.unix_timestamp >= transaction.expires_at)
+    }
+
+    fn expire_transaction<'a>(
+        program_id: &Pubkey,
+        payer: &AccountInfo<'a>,
+        client_program_derived_account: &AccountInfo<'a>,
+        accounts: &[AccountInfo<'a>],
+        account_data: &mut MultiSigWalletState
+    ) -> Result<bool, ProgramError> {
+        if !Self::is_expired(&account_data.transaction)? {
+            return Ok(false)
+        }
+
+        msg!("Transaction expired");
+        account_data.transaction.status = TransactionStatus::Expired;
+        Self::emit_transaction_closed(account_data)?;
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Self::write_closing_receipt(program_id, payer, client_program_derived_account, accounts, account_data)?;
+
+        Ok(true)
+    }
+
+    fn validate_guardians(
+        program_id: &Pubkey,
+        wallet: &Pubkey,
+        guardians: &[Pubkey],
+        guardian_threshold: u64,
+        inactivity_period: i64,
+        recovery_delay: i64
+    ) -> ProgramResult {
+        if guardians.is_empty() && guardian_threshold == 0 {
+            return Ok(())
+        }
+
+        if guardians.len() > MAX_GUARDIANS || guardian_threshold < 1 || guardians.len() < guardian_threshold as usize {
+            msg!("Invalid guardians length or threshold");
+            return Err(MultiSigWalletError::InvalidGuardians.into())
+        }
+
+        // Duplicates are rejected, so the length check above also bounds the
+        // threshold by the number of distinct guardians.
+        if guardians.iter().enumerate().any(|(index, guardian)| guardians[..index].contains(guardian)) {
+            msg!("Duplicate guardian");
+            return Err(MultiSigWalletError::InvalidGuardians.into())
+        }
+
+        let (vault_address, _bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), wallet.as_ref(), &[0]], program_id);
+
+        if guardians.iter().any(|guardian| *guardian == Pubkey::default() || guardian == wallet || *guardian == vault_address) {
+            msg!("Guardian cannot be the default key, the wallet or its vault");
+            return Err(MultiSigWalletError::InvalidGuardians.into())
+        }
+
+        if inactivity_period <= 0 || recovery_delay < 0 {
+            msg!("Invalid inactivity period or recovery delay");
+            return Err(MultiSigWalletError::InvalidGuardians.into())
+        }
+
+        Ok(())
+    }
+
+    fn validate_owner_thresholds(
+        account_data: &MultiSigWalletState,
+        owners_len: usize
+    ) -> ProgramResult {
+        let owners_range = 1..=owners_len as u64;
+
+        if account_data.allowlist_bypass_threshold != 0 && !owners_range.contains(&account_data.allowlist_bypass_threshold) {
+            msg!("Allowlist bypass threshold higher than the number of owners");
+            return Err(MultiSigWalletError::InvalidThreshold.into())
+        }
+
+        if account_data.veto_threshold != 0 && !owners_range.contains(&account_data.veto_threshold) {
+            msg!("Veto threshold higher than the number of owners");
+            return Err(MultiSigWalletError::InvalidThreshold.into())
+        }
+
+        Ok(())
+    }
+
+    fn check_allowed_destination(
+        account_data: &MultiSigWalletState,
+        destination: &Pubkey
+    ) -> ProgramResult {
+        if !Self::is_allowed_destination(account_data, destination) {
+            if account_data.allowlist_bypass_threshold == 0 {
+                msg!("Destination not in the allowlist");
+                return Err(MultiSigWalletError::DestinationNotAllowed.into())
+            }
+
+            if Self::current_approvals(account_data) < account_data.allowlist_bypass_threshold as usize {
+                msg!("Not enough approvals to send outside the allowlist");
+                return Err(MultiSigWalletError::NotEnoughApprovals.into())
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_allowed_destination(
+        account_data: &MultiSigWalletState,
+        destination: &Pubkey
+    ) -> bool {
+        !account_data.allowlist_enabled || account_data.allowlist.iter().any(|address| address == destination)
+    }
+
+    fn record_owner_activity(
+        account_data: &mut MultiSigWalletState,
+    ) -> ProgramResult {
+        account_data.last_activity = Clock::get()?.unix_timestamp;
+
+        if account_data.recovery.is_active {
+            msg!("Recovery cancelled by owner activity");
+            Self::clear_recovery_state(account_data);
+            MultiSigWalletEvent::RecoveryCancelled.emit(account_data)?;
+        }
+
+        Ok(())
+    }
+
+    fn initiate_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        owners: Vec<Pubkey>,
+        threshold: u64
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.guardians.iter().any(|guardian| guardian == initializer.key) {
+            msg!("Initializer not a guardian");
+            return Err(MultiSigWalletError::InvalidGuardian.into());
+        }
+
+        if account_data.recovery.is_active {
+            msg!("Recovery already in progress");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+
+        if now - account_data.last_activity < account_data.inactivity_period {
+            msg!("Owners have not been inactive long enough");
+            return Err(MultiSigWalletError::RecoveryNotAvailable.into());
+        }
+
+        if owners.len() < 2 || owners.len() > MAX_OWNERS {
+            msg!("Invalid owners length");
+            return Err(MultiSigWalletError::InvalidOwnersLength.into())
+        }
+
+        if !(2..=owners.len() as u64).contains(&threshold) {
+            msg!("Invalid threshold");
+            return Err(MultiSigWalletError::InvalidThreshold.into())
+        }
+
+        Self::validate_owners(&owners, client_program_derived_account.key)?;
+        Self::validate_owner_thresholds(&account_data, owners.len())?;
+
+        account_data.recovery.is_active = true;
+        account_data.recovery.owners = owners;
+        account_data.recovery.threshold = threshold;
+        account_data.recovery.approvals = vec![*initializer.key];
+        account_data.recovery.started_at = now;
+
+        MultiSigWalletEvent::RecoveryInitiated {
+            guardian: *initializer.key,
+            owners: account_data.recovery.owners.clone(),
+            threshold
+        }.emit(&mut account_data)?;
+
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn approve_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.guardians.iter().any(|guardian| guardian == initializer.key) {
+            msg!("Initializer not a guardian");
+            return Err(MultiSigWalletError::InvalidGuardian.into());
+        }
+
+        if !account_data.recovery.is_active {
+            msg!("No recovery in progress");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        if account_data.recovery.approvals.iter().any(|guardian| guardian == initializer.key) {
+            msg!("Initializer already approved the recovery");
+            return Err(MultiSigWalletError::InvalidInstruction.into());
+        }
+
+        account_data.recovery.approvals.append(&mut vec![*initializer.key]);
+
+        MultiSigWalletEvent::RecoveryApproved {
+            guardian: *initializer.key
+        }.emit(&mut account_data)?;
+
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn execute_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.guardians.iter().any(|guardian| guardian == initializer.key) {
+            msg!("Initializer not a guardian");
+            return Err(MultiSigWalletError::InvalidGuardian.into());
+        }
+
+        if !account_data.recovery.is_active {
+            msg!("No recovery in progress");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        if account_data.recovery.approvals.len() < account_data.guardian_threshold as usize {
+            msg!("Not enough approvals");
+            return Err(MultiSigWalletError::NotEnoughApprovals.into());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+
+        if now - account_data.recovery.started_at < account_data.recovery_delay {
+            msg!("Recovery delay has not passed yet");
+            return Err(MultiSigWalletError::RecoveryNotAvailable.into());
+        }
+
+        let previous_owners = account_data.owners.clone();
+
+        account_data.owners = account_data.recovery.owners.clone();
+        account_data.threshold = account_data.recovery.threshold;
+        account_data.last_activity = now;
+
+        Self::update_owner_registries(program_id, initializer, client_program_derived_account, accounts, &mut account_data, &previous_owners)?;
+
+        if account_data.transaction.status.is_pending() {
+            account_data.transaction.status = TransactionStatus::Cancelled;
+            Self::emit_transaction_closed(&mut account_data)?;
+            Self::write_closing_receipt(program_id, initializer, client_program_derived_account, accounts, &account_data)?;
+        }
+
+        Self::clear_recovery_state(&mut account_data);
+
+        MultiSigWalletEvent::RecoveryExecuted {
+            owners: account_data.owners.clone(),
+            threshold: account_data.threshold
+        }.emit(&mut account_data)?;
+
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn cancel_recovery(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
+            msg!("Initializer not an owner");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        if !account_data.recovery.is_active {
+            msg!("No recovery in progress");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        Self::record_owner_activity(&mut account_data)?;
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn pause(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        let is_owner = account_data.owners.iter().any(|owner| owner == initializer.key);
+
+        if !is_owner && !account_data.guardians.iter().any(|guardian| guardian == initializer.key) {
+            msg!("Initializer neither an owner nor a guardian");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        if account_data.is_paused {
+            msg!("Wallet already paused");
+            return Err(MultiSigWalletError::WalletPaused.into());
+        }
+
+        if is_owner {
+            Self::record_owner_activity(&mut account_data)?;
+        }
+
+        account_data.is_paused = true;
+        account_data.paused_by = *initializer.key;
+
+        MultiSigWalletEvent::Paused {
+            paused_by: *initializer.key
+        }.emit(&mut account_data)?;
+
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn spend_within_limit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        vault_index: u8
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let initializer = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+        let to_account = next_account_info(account_info_iter)?;
+        let vault = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !initializer.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if !account_data.owners.iter().any(|owner| owner == initializer.key) {
+            msg!("Initializer not an owner");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        if account_data.is_paused {
+            msg!("Wallet paused");
+            return Err(MultiSigWalletError::WalletPaused.into());
+        }
+
+        let (vault_address, bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid seeds for vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        if amount == 0 {
+            msg!("Amount needs to be higher than 0");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if *to_account.key == *client_program_derived_account.key || *to_account.key == *vault.key {
+            msg!("Cannot send to Self");
+            return Err(ProgramError::InvalidInstructionData)
+        }
+
+        if !Self::is_allowed_destination(&account_data, to_account.key) {
+            msg!("Destination not in the allowlist");
+            return Err(MultiSigWalletError::DestinationNotAllowed.into())
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let spending_limit = &mut account_data.spending_limit;
+
+        if spending_limit.amount == 0 {
+            msg!("No spending limit set");
+            return Err(MultiSigWalletError::SpendingLimitExceeded.into());
+        }
+
+        if now - spending_limit.period_start >= spending_limit.period {
+            spending_limit.spent = 0;
+            spending_limit.period_start = now;
+        }
+
+        if spending_limit.spent.saturating_add(amount) > spending_limit.amount {
+            msg!("Amount exceeds the remaining allowance");
+            return Err(MultiSigWalletError::SpendingLimitExceeded.into());
+        }
+
+        spending_limit.spent += amount;
+
+        MultiSigWalletEvent::SpentWithinLimit {
+            owner: *initializer.key,
+            to_address: *to_account.key,
+            vault_index,
+            amount
+        }.emit(&mut account_data)?;
+
+        Self::record_owner_activity(&mut account_data)?;
+
+        invoke_signed(
+            &system_instruction::transfer(vault.key, to_account.key, amount),
+            &[vault.clone(), to_account.clone(), system_program.clone()],
+            &[&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[vault_index], &[bump_seed]]],
+        )?;
+
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn clear_recovery_state(
+        account_data: &mut MultiSigWalletState,
+    ) {
+        account_data.recovery.is_active = false;
+        account_data.recovery.owners = Vec::new();
+        account_data.recovery.threshold = 0;
+        account_data.recovery.approvals = Vec::new();
+        account_data.recovery.started_at = 0;
+    }
+
+    fn claim_scheduled_payment(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+        let schedule = next_account_info(account_info_iter)?;
+        let vault = next_account_info(account_info_iter)?;
+        let recipient = next_account_info(account_info_iter)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if account_data.is_paused {
+            msg!("Wallet paused");
+            return Err(MultiSigWalletError::WalletPaused.into());
+        }
+
+        let mut schedule_data = Self::load_schedule(program_id, client_program_derived_account, schedule)?;
+
+        if schedule_data.is_cancelled {
+            msg!("Schedule cancelled");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        if schedule_data.recipient != *recipient.key {
+            msg!("The provided address does not match with the stored address");
+            return Err(MultiSigWalletError::InvalidInstruction.into());
+        }
+
+        // The allowlist may have changed since the schedule was approved.
+        if !Self::is_allowed_destination(&account_data, recipient.key) {
+            msg!("Destination not in the allowlist");
+            return Err(MultiSigWalletError::DestinationNotAllowed.into())
+        }
+
+        let (vault_address, vault_bump_seed) = Pubkey::find_program_address(&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[schedule_data.vault_index]], program_id);
+
+        if vault_address != *vault.key {
+            msg!("Invalid seeds for vault");
+            return Err(MultiSigWalletError::InvalidVault.into())
+        }
+
+        let last_due_at = Clock::get()?.unix_timestamp.min(schedule_data.end_at);
+
+        if schedule_data.next_payment_at > last_due_at {
+            msg!("No scheduled payment due");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        let payments = ((last_due_at - schedule_data.next_payment_at) / schedule_data.interval + 1) as u64;
+        let due = payments.checked_mul(schedule_data.amount).ok_or(ProgramError::InvalidArgument)?;
+
+        schedule_data.payments_made += payments;
+        schedule_data.next_payment_at = (payments as i64).checked_mul(schedule_data.interval)
+            .and_then(|elapsed| schedule_data.next_payment_at.checked_add(elapsed))
+            .ok_or(ProgramError::InvalidArgument)?;
+
+        if vault.lamports() < due {
+            msg!("Vault has insufficient funds");
+            return Err(ProgramError::InsufficientFunds)
+        }
+
+        invoke_signed(
+            &system_instruction::transfer(vault.key, recipient.key, due),
+            &[vault.clone(), recipient.clone(), system_program.clone()],
+            &[&[b"vault".as_ref(), client_program_derived_account.key.as_ref(), &[schedule_data.vault_index], &[vault_bump_seed]]],
+        )?;
+
+        schedule_data.serialize(&mut &mut schedule.data.borrow_mut()[..])?;
+
+        MultiSigWalletEvent::ScheduledPaymentClaimed {
+            schedule: *schedule.key,
+            recipient: *recipient.key,
+            amount: due,
+            payments_made: schedule_data.payments_made
+        }.emit(&mut account_data)?;
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
+    }
+
+    fn withdraw_vested(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo]
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+
+        let recipient = next_account_info(account_info_iter)?;
+        let base = next_account_info(account_info_iter)?;
+        let client_program_derived_account = next_account_info(account_info_iter)?;
+        let vesting = next_account_info(account_info_iter)?;
+
+        if client_program_derived_account.owner != program_id {
+            msg!("PDA not owned by the program");
+            return Err(ProgramError::IllegalOwner)
+        }
+
+        if !recipient.is_signer {
+            msg!("Missing required signature");
+            return Err(ProgramError::MissingRequiredSignature)
+        }
+
+        let (program_derived_account, _bump_seed) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.key.as_ref()], program_id);
+
+        if program_derived_account != *client_program_derived_account.key {
+            msg!("Invalid seeds for PDA");
+            return Err(MultiSigWalletError::InvalidPDA.into())
+        }
+
+        let mut account_data = try_from_slice_unchecked::<MultiSigWalletState>(&client_program_derived_account.data.borrow()).map_err(|_| ProgramError::InvalidAccountData)?;
+
+        if !account_data.is_initialized() {
+            msg!("Wallet not initialized");
+            return Err(MultiSigWalletError::UninitializedAccount.into());
+        }
+
+        if account_data.is_paused {
+            msg!("Wallet paused");
+            return Err(MultiSigWalletError::WalletPaused.into());
+        }
+
+        let mut vesting_data = Self::load_vesting(program_id, client_program_derived_account, vesting)?;
+
+        if vesting_data.recipient != *recipient.key {
+            msg!("Initializer not the vesting recipient");
+            return Err(MultiSigWalletError::InvalidOwner.into());
+        }
+
+        let amount = vesting_data.vested_at(Clock::get()?.unix_timestamp) - vesting_data.withdrawn;
+
+        if amount == 0 {
+            msg!("Nothing vested to withdraw");
+            return Err(MultiSigWalletError::UnexpectedInstruction.into());
+        }
+
+        **vesting.try_borrow_mut_lamports()? -= amount;
+        **recipient.try_borrow_mut_lamports()? += amount;
+
+        vesting_data.withdrawn += amount;
+        vesting_data.serialize(&mut &mut vesting.data.borrow_mut()[..])?;
+
+        MultiSigWalletEvent::VestedWithdrawn {
+            vesting: *vesting.key,
+            recipient: *recipient.key,
+            amount
+        }.emit(&mut account_data)?;
+        account_data.serialize(&mut &mut client_program_derived_account.data.borrow_mut()[..])?;
+
+        Ok(())
     }
 }
\ No newline at end of file
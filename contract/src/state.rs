@@ -1,4 +1,6 @@
 use solana_program::{
+    hash::hash,
+    program_error::ProgramError,
     pubkey::Pubkey,
     program_pack::{
         IsInitialized,
@@ -10,16 +12,168 @@ use borsh::{
     BorshDeserialize
 };
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub const MAX_OWNERS: usize = 10;
+pub const MAX_GUARDIANS: usize = 3;
+pub const MAX_ALLOWLIST: usize = 10;
+pub const MAX_TRANSACTION_ACCOUNTS: usize = 4;
+pub const MAX_BATCH_ACTIONS: usize = 20;
+pub const MAX_BATCH_ACTION_ACCOUNTS: usize = 6;
+pub const MAX_BATCH_ACTION_DATA: usize = 96;
+pub const MAX_DESCRIPTION_LEN: usize = 128;
+pub const MAX_REGISTRY_WALLETS: usize = 64;
+
+const PUBKEY_LEN: usize = 32;
+const OWNERS_LEN: usize = 4 + (MAX_OWNERS * PUBKEY_LEN);
+const GUARDIANS_LEN: usize = 4 + (MAX_GUARDIANS * PUBKEY_LEN);
+const ALLOWLIST_LEN: usize = 4 + (MAX_ALLOWLIST * PUBKEY_LEN);
+const TRANSACTION_ACCOUNTS_LEN: usize = 4 + (MAX_TRANSACTION_ACCOUNTS * PUBKEY_LEN);
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum TransactionStatus {
+    #[default]
+    Empty,
+    Active,
+    Approved,
+    Executed,
+    Rejected,
+    Cancelled,
+    Expired
+}
+
+impl TransactionStatus {
+    pub fn is_pending(&self) -> bool {
+        *self == TransactionStatus::Active || *self == TransactionStatus::Approved
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum TransactionVariant {
+    #[default]
+    SetOwners,
+    SetThreshold,
+    Send,
+    SetGuardians,
+    Unpause,
+    SetSpendingLimit,
+    SetAllowlist,
+    SetVetoThreshold,
+    SetExecutionPolicy,
+    AddOwner,
+    RemoveOwner,
+    SwapOwner,
+    Reconfigure,
+    UpgradeProgram,
+    SetUpgradeAuthority,
+    DelegateStake,
+    DeactivateStake,
+    WithdrawStake,
+    SplitStake,
+    MergeStake,
+    MintTokens,
+    BurnTokens,
+    SetTokenAuthority,
+    FreezeTokenAccount,
+    ThawTokenAccount,
+    TransferTokens,
+    WrapSol,
+    UnwrapSol,
+    Batch,
+    CreateSchedule,
+    CancelSchedule,
+    CreateVesting,
+    CancelVesting
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum RentPayer {
+    #[default]
+    None,
+    Vault,
+    Executor
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone)]
 pub struct TransactionState {
-    pub is_executed: bool,
-    pub variant: u8,
+    pub id: u64,
+    pub status: TransactionStatus,
+    pub variant: TransactionVariant,
+    pub proposer: Pubkey,
     pub signers: Vec<Pubkey>,
     pub opponents: Vec<Pubkey>,
     pub to_address: Pubkey,
     pub amount: u64,
     pub owners: Vec<Pubkey>,
-    pub threshold: u64
+    pub threshold: u64,
+    pub vault_index: u8,
+    pub guardians: Vec<Pubkey>,
+    pub guardian_threshold: u64,
+    pub inactivity_period: i64,
+    pub recovery_delay: i64,
+    pub period: i64,
+    pub allowlist_enabled: bool,
+    pub allowlist: Vec<Pubkey>,
+    pub allowlist_bypass_threshold: u64,
+    pub veto_threshold: u64,
+    pub expires_at: i64,
+    pub cancellers: Vec<Pubkey>,
+    pub owners_only_execution: bool,
+    pub owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub accounts: Vec<Pubkey>,
+    pub authority_type: u8,
+    pub recipient_rent_payer: RentPayer,
+    pub batch_hash: [u8; 32],
+    pub batch_chunked: bool,
+    pub batch_progress: u64,
+    pub start_at: i64,
+    pub end_at: i64,
+    pub cliff_at: i64,
+    pub description: String,
+    pub reference: Option<[u8; 32]>,
+    pub hash: [u8; 32]
+}
+
+impl TransactionState {
+    pub const LEN: usize = 8 + 1 + 1 + PUBKEY_LEN + OWNERS_LEN + OWNERS_LEN + PUBKEY_LEN + 8 + OWNERS_LEN + 8 + 1 + GUARDIANS_LEN + 8 + 8 + 8 + 8 + 1 + ALLOWLIST_LEN + 8 + 8 + 8 + OWNERS_LEN + 1 + PUBKEY_LEN + PUBKEY_LEN + TRANSACTION_ACCOUNTS_LEN + 1 + 1 + 32 + 1 + 8 + 8 + 8 + 8 + 4 + MAX_DESCRIPTION_LEN + 1 + 32 + 32;
+
+    pub fn content_hash(&self) -> Result<[u8; 32], ProgramError> {
+        let content = TransactionState {
+            status: TransactionStatus::Empty,
+            signers: Vec::new(),
+            opponents: Vec::new(),
+            cancellers: Vec::new(),
+            batch_progress: 0,
+            hash: [0; 32],
+            ..self.clone()
+        };
+
+        Ok(hash(&content.try_to_vec()?).to_bytes())
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RecoveryState {
+    pub is_active: bool,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u64,
+    pub approvals: Vec<Pubkey>,
+    pub started_at: i64
+}
+
+impl RecoveryState {
+    pub const LEN: usize = 1 + OWNERS_LEN + 8 + GUARDIANS_LEN + 8;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SpendingLimitState {
+    pub amount: u64,
+    pub period: i64,
+    pub spent: u64,
+    pub period_start: i64
+}
+
+impl SpendingLimitState {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
@@ -28,7 +182,27 @@ pub struct MultiSigWalletState {
     pub owners: Vec<Pubkey>,
     pub threshold: u64,
     pub seed: Pubkey,
-    pub transaction: TransactionState
+    pub transaction_count: u64,
+    pub transaction: TransactionState,
+    pub guardians: Vec<Pubkey>,
+    pub guardian_threshold: u64,
+    pub inactivity_period: i64,
+    pub recovery_delay: i64,
+    pub last_activity: i64,
+    pub recovery: RecoveryState,
+    pub is_paused: bool,
+    pub paused_by: Pubkey,
+    pub spending_limit: SpendingLimitState,
+    pub allowlist_enabled: bool,
+    pub allowlist: Vec<Pubkey>,
+    pub allowlist_bypass_threshold: u64,
+    pub veto_threshold: u64,
+    pub owners_only_execution: bool,
+    pub audit_hash: [u8; 32]
+}
+
+impl MultiSigWalletState {
+    pub const LEN: usize = 1 + OWNERS_LEN + 8 + PUBKEY_LEN + 8 + TransactionState::LEN + GUARDIANS_LEN + 8 + 8 + 8 + 8 + RecoveryState::LEN + 1 + PUBKEY_LEN + SpendingLimitState::LEN + 1 + ALLOWLIST_LEN + 8 + 8 + 1 + 32;
 }
 
 impl Sealed for MultiSigWalletState {}
@@ -38,3 +212,188 @@ impl IsInitialized for MultiSigWalletState {
         self.is_initialized
     }
 }
+
+/// Leading fields of a wallet created before funds were held in vaults, these
+/// accounts were allocated with a fixed size and never hold a vault index.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct LegacyMultiSigWalletState {
+    pub is_initialized: bool,
+    pub owners: Vec<Pubkey>
+}
+
+impl LegacyMultiSigWalletState {
+    pub const LEN: usize = 1 + (4 + (3 * 32)) + 8 + (4 + 32) + 1 + 8 + (4 + (3 * 32)) + (4 + (3 * 32)) + (4 + 32) + 8 + (4 + (3 * 32)) + 8;
+}
+
+impl Sealed for LegacyMultiSigWalletState {}
+
+impl IsInitialized for LegacyMultiSigWalletState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct TransactionReceiptState {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub executor: Pubkey,
+    pub slot: u64,
+    pub transaction: TransactionState
+}
+
+impl TransactionReceiptState {
+    pub const LEN: usize = 1 + PUBKEY_LEN + PUBKEY_LEN + 8 + TransactionState::LEN;
+}
+
+impl Sealed for TransactionReceiptState {}
+
+impl IsInitialized for TransactionReceiptState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct OwnerRegistryState {
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    pub wallets: Vec<Pubkey>
+}
+
+impl OwnerRegistryState {
+    pub const LEN: usize = 1 + PUBKEY_LEN + 4 + (MAX_REGISTRY_WALLETS * PUBKEY_LEN);
+}
+
+impl Sealed for OwnerRegistryState {}
+
+impl IsInitialized for OwnerRegistryState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ScheduledPaymentState {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub transaction_id: u64,
+    pub recipient: Pubkey,
+    pub vault_index: u8,
+    pub amount: u64,
+    pub interval: i64,
+    pub next_payment_at: i64,
+    pub end_at: i64,
+    pub payments_made: u64,
+    pub is_cancelled: bool
+}
+
+impl ScheduledPaymentState {
+    pub const LEN: usize = 1 + PUBKEY_LEN + 8 + PUBKEY_LEN + 1 + 8 + 8 + 8 + 8 + 8 + 1;
+}
+
+impl Sealed for ScheduledPaymentState {}
+
+impl IsInitialized for ScheduledPaymentState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct VestingState {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub transaction_id: u64,
+    pub recipient: Pubkey,
+    pub vault_index: u8,
+    pub total: u64,
+    pub withdrawn: u64,
+    pub start_at: i64,
+    pub cliff_at: i64,
+    pub end_at: i64,
+    pub is_cancelled: bool
+}
+
+impl VestingState {
+    pub const LEN: usize = 1 + PUBKEY_LEN + 8 + PUBKEY_LEN + 1 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn vested_at(&self, timestamp: i64) -> u64 {
+        if self.is_cancelled || timestamp >= self.end_at {
+            return self.total
+        }
+
+        if timestamp < self.cliff_at {
+            return 0
+        }
+
+        let elapsed = (timestamp - self.start_at) as u128;
+        let duration = (self.end_at - self.start_at) as u128;
+
+        (self.total as u128 * elapsed / duration) as u64
+    }
+}
+
+impl Sealed for VestingState {}
+
+impl IsInitialized for VestingState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub enum BatchActionKind {
+    #[default]
+    Send,
+    TransferTokens,
+    SetThreshold,
+    SetVetoThreshold,
+    Invoke
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq)]
+pub struct BatchAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Clone, PartialEq)]
+pub struct BatchActionState {
+    pub kind: BatchActionKind,
+    pub to_address: Pubkey,
+    pub amount: u64,
+    pub vault_index: u8,
+    pub mint: Pubkey,
+    pub accounts: Vec<BatchAccountMeta>,
+    pub data: Vec<u8>
+}
+
+impl BatchActionState {
+    pub const LEN: usize = 1 + PUBKEY_LEN + 8 + 1 + PUBKEY_LEN + 4 + (MAX_BATCH_ACTION_ACCOUNTS * (PUBKEY_LEN + 1 + 1)) + 4 + MAX_BATCH_ACTION_DATA;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct BatchState {
+    pub is_initialized: bool,
+    pub wallet: Pubkey,
+    pub transaction_id: u64,
+    pub actions: Vec<BatchActionState>
+}
+
+impl BatchState {
+    pub const LEN: usize = 1 + PUBKEY_LEN + 8 + 4 + (MAX_BATCH_ACTIONS * BatchActionState::LEN);
+
+    pub fn actions_hash(&self) -> Result<[u8; 32], ProgramError> {
+        Ok(hash(&self.actions.try_to_vec()?).to_bytes())
+    }
+}
+
+impl Sealed for BatchState {}
+
+impl IsInitialized for BatchState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
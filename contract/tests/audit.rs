@@ -0,0 +1,73 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::{
+    INACTIVITY_PERIOD,
+    TestWallet
+};
+use fresh_guacamole::{
+    event::MultiSigWalletEvent,
+    instruction::CreateTransactionPayload,
+    state::{
+        TransactionStatus,
+        TransactionVariant
+    }
+};
+use solana_program::{
+    hash::hashv,
+    pubkey::Pubkey
+};
+use solana_sdk::signature::Signer;
+
+fn chain(audit_hash: [u8; 32], event: MultiSigWalletEvent) -> [u8; 32] {
+    hashv(&[&audit_hash, &event.try_to_vec().unwrap()]).to_bytes()
+}
+
+#[tokio::test]
+async fn audit_hash_replays_from_the_emitted_events() {
+    let mut test_wallet = TestWallet::start().await;
+    let payer = test_wallet.context.payer.pubkey();
+    let second_owner = test_wallet.second_owner.pubkey();
+
+    let mut audit_hash = chain([0; 32], MultiSigWalletEvent::WalletCreated {
+        owners: vec![payer, second_owner],
+        threshold: 2,
+        guardians: vec![test_wallet.guardian.pubkey()],
+        guardian_threshold: 1,
+        inactivity_period: INACTIVITY_PERIOD,
+        recovery_delay: 0,
+        owners_only_execution: false
+    });
+    assert_eq!(test_wallet.state().await.audit_hash, audit_hash);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::SetVetoThreshold,
+        veto_threshold: 1,
+        ..Default::default()
+    }, Pubkey::default(), vec![]).await;
+    let reference = test_wallet.reference().await;
+
+    audit_hash = chain(audit_hash, MultiSigWalletEvent::TransactionCreated {
+        id: reference.transaction_id,
+        variant: TransactionVariant::SetVetoThreshold,
+        proposer: payer,
+        hash: reference.transaction_hash
+    });
+    assert_eq!(test_wallet.state().await.audit_hash, audit_hash);
+
+    test_wallet.confirm().await;
+    test_wallet.try_execute(Pubkey::default(), vec![]).await.unwrap();
+
+    audit_hash = chain(audit_hash, MultiSigWalletEvent::TransactionConfirmed {
+        id: reference.transaction_id,
+        owner: second_owner
+    });
+    audit_hash = chain(audit_hash, MultiSigWalletEvent::VetoThresholdSet {
+        veto_threshold: 1
+    });
+    audit_hash = chain(audit_hash, MultiSigWalletEvent::TransactionClosed {
+        id: reference.transaction_id,
+        status: TransactionStatus::Executed
+    });
+    assert_eq!(test_wallet.state().await.audit_hash, audit_hash);
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::TestWallet;
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::{
+        BatchActionState,
+        TransactionStatus,
+        TransactionVariant
+    }
+};
+use solana_program::{
+    instruction::{
+        AccountMeta,
+        InstructionError
+    },
+    pubkey::Pubkey,
+    system_program
+};
+use solana_sdk::transaction::TransactionError;
+
+const AMOUNT: u64 = 1_000_000;
+
+/// Proposes and confirms a batch sending `AMOUNT` to each recipient, `to` is
+/// only the destination account of the proposal.
+async fn propose_batch(test_wallet: &mut TestWallet, to: Pubkey, recipients: &[Pubkey], batch_chunked: bool) -> Pubkey {
+    let transaction_id = test_wallet.next_transaction_id().await;
+    let batch = test_wallet.derived_address(b"batch", transaction_id);
+
+    let actions = recipients.iter().map(|recipient| BatchActionState {
+        to_address: *recipient,
+        amount: AMOUNT,
+        ..Default::default()
+    }).collect();
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Batch,
+        actions,
+        batch_chunked,
+        ..Default::default()
+    }, to, vec![
+        AccountMeta::new(batch, false),
+        AccountMeta::new_readonly(system_program::id(), false)
+    ]).await;
+    test_wallet.confirm().await;
+
+    batch
+}
+
+fn send_action_accounts(test_wallet: &TestWallet, recipient: Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(test_wallet.vault(0), false),
+        AccountMeta::new(recipient, false)
+    ]
+}
+
+#[tokio::test]
+async fn batch_runs_all_actions_at_once_unless_chunked() {
+    let mut test_wallet = TestWallet::start().await;
+    let to = Pubkey::new_unique();
+    let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let batch = propose_batch(&mut test_wallet, to, &recipients, false).await;
+
+    let mut execute_accounts = vec![AccountMeta::new(batch, false)];
+    execute_accounts.extend(send_action_accounts(&test_wallet, recipients[0]));
+    let result = test_wallet.try_execute(to, execute_accounts).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    let mut execute_accounts = vec![AccountMeta::new(batch, false)];
+    execute_accounts.extend(send_action_accounts(&test_wallet, recipients[0]));
+    execute_accounts.extend(send_action_accounts(&test_wallet, recipients[1]));
+    test_wallet.try_execute(to, execute_accounts).await.unwrap();
+
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Executed);
+    assert_eq!(test_wallet.balance(recipients[0]).await, AMOUNT);
+    assert_eq!(test_wallet.balance(recipients[1]).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn chunked_batch_executes_over_several_transactions() {
+    let mut test_wallet = TestWallet::start().await;
+    let to = Pubkey::new_unique();
+    let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let batch = propose_batch(&mut test_wallet, to, &recipients, true).await;
+
+    for (executed, recipient) in recipients.iter().enumerate() {
+        let mut execute_accounts = vec![AccountMeta::new(batch, false)];
+        execute_accounts.extend(send_action_accounts(&test_wallet, *recipient));
+        test_wallet.try_execute(to, execute_accounts).await.unwrap();
+
+        let transaction = test_wallet.state().await.transaction;
+        assert_eq!(transaction.batch_progress, executed as u64 + 1);
+        assert_eq!(test_wallet.balance(*recipient).await, AMOUNT);
+    }
+
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Executed);
+}
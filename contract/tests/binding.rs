@@ -0,0 +1,118 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::{
+        CreateTransactionPayload,
+        TransactionReferencePayload
+    },
+    state::{
+        TransactionStatus,
+        TransactionVariant
+    }
+};
+use solana_program::{
+    instruction::AccountMeta,
+    pubkey::Pubkey
+};
+use solana_sdk::{
+    signature::Signer,
+    transport
+};
+
+// MultiSigWalletError::TransactionMismatch
+const TRANSACTION_MISMATCH: u32 = 21;
+
+const AMOUNT: u64 = 1_000_000;
+
+async fn propose_send(test_wallet: &mut TestWallet, recipient: Pubkey, amount: u64) {
+    let (propose_accounts, _) = test_wallet.send_accounts(0);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount,
+        ..Default::default()
+    }, recipient, propose_accounts).await;
+}
+
+async fn try_confirm(test_wallet: &mut TestWallet, reference: TransactionReferencePayload) -> transport::Result<()> {
+    let accounts = test_wallet.wallet_accounts(&test_wallet.second_owner.pubkey());
+    let instruction = wallet_instruction(&test_wallet.program_id, 2, reference, accounts);
+
+    common::try_process(&mut test_wallet.context, instruction, &[&test_wallet.second_owner]).await
+}
+
+#[tokio::test]
+async fn proposals_get_increasing_ids_and_content_hashes() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    propose_send(&mut test_wallet, recipient, AMOUNT).await;
+    let first = test_wallet.reference().await;
+
+    let payer = test_wallet.context.payer.pubkey();
+    let accounts = test_wallet.closing_wallet_accounts(&payer).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 5, (), accounts);
+    common::process(&mut test_wallet.context, instruction, &[]).await;
+
+    propose_send(&mut test_wallet, recipient, AMOUNT * 2).await;
+    let second = test_wallet.reference().await;
+
+    assert_eq!(first.transaction_id, 1);
+    assert_eq!(second.transaction_id, 2);
+    assert_ne!(first.transaction_hash, second.transaction_hash);
+}
+
+#[tokio::test]
+async fn confirmation_for_a_different_proposal_is_rejected() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    propose_send(&mut test_wallet, recipient, AMOUNT).await;
+    let reference = test_wallet.reference().await;
+
+    let result = try_confirm(&mut test_wallet, TransactionReferencePayload {
+        transaction_id: reference.transaction_id + 1,
+        ..reference
+    }).await;
+    assert_wallet_error(result, TRANSACTION_MISMATCH);
+
+    let result = try_confirm(&mut test_wallet, TransactionReferencePayload {
+        transaction_hash: [0; 32],
+        ..reference
+    }).await;
+    assert_wallet_error(result, TRANSACTION_MISMATCH);
+
+    try_confirm(&mut test_wallet, reference).await.unwrap();
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Approved);
+}
+
+#[tokio::test]
+async fn execution_for_a_different_proposal_is_rejected() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    propose_send(&mut test_wallet, recipient, AMOUNT).await;
+    test_wallet.confirm().await;
+    let reference = test_wallet.reference().await;
+
+    let (_, execute_accounts) = test_wallet.send_accounts(0);
+    let payer = test_wallet.context.payer.pubkey();
+    let mut accounts = test_wallet.wallet_accounts(&payer);
+    accounts.push(AccountMeta::new(recipient, false));
+    accounts.extend(test_wallet.closing_accounts().await);
+    accounts.extend(execute_accounts);
+
+    let instruction = wallet_instruction(&test_wallet.program_id, 4, TransactionReferencePayload {
+        transaction_hash: [0; 32],
+        ..reference
+    }, accounts);
+    let result = common::try_process(&mut test_wallet.context, instruction, &[]).await;
+    assert_wallet_error(result, TRANSACTION_MISMATCH);
+
+    assert_eq!(test_wallet.balance(recipient).await, 0);
+}
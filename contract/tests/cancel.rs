@@ -0,0 +1,92 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::{
+        TransactionStatus,
+        TransactionVariant
+    }
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{
+    signature::Signer,
+    transport
+};
+
+// MultiSigWalletError::InvalidInstruction
+const INVALID_INSTRUCTION: u32 = 7;
+
+async fn propose_send(test_wallet: &mut TestWallet) {
+    let (propose_accounts, _) = test_wallet.send_accounts(0);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount: 1_000_000,
+        ..Default::default()
+    }, Pubkey::new_unique(), propose_accounts).await;
+}
+
+async fn cancel_as_proposer(test_wallet: &mut TestWallet) -> transport::Result<()> {
+    let payer = test_wallet.context.payer.pubkey();
+    let accounts = test_wallet.closing_wallet_accounts(&payer).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 5, (), accounts);
+
+    common::try_process(&mut test_wallet.context, instruction, &[]).await
+}
+
+async fn cancel_as_second_owner(test_wallet: &mut TestWallet) {
+    let second_owner = test_wallet.second_owner.pubkey();
+    let accounts = test_wallet.closing_wallet_accounts(&second_owner).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 5, (), accounts);
+
+    common::process(&mut test_wallet.context, instruction, &[&test_wallet.second_owner]).await;
+}
+
+#[tokio::test]
+async fn proposer_cancels_before_approval() {
+    let mut test_wallet = TestWallet::start().await;
+
+    propose_send(&mut test_wallet).await;
+    cancel_as_proposer(&mut test_wallet).await.unwrap();
+
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Cancelled);
+}
+
+#[tokio::test]
+async fn other_owner_only_votes_to_cancel() {
+    let mut test_wallet = TestWallet::start().await;
+
+    propose_send(&mut test_wallet).await;
+    cancel_as_second_owner(&mut test_wallet).await;
+
+    let transaction = test_wallet.state().await.transaction;
+    assert_eq!(transaction.status, TransactionStatus::Active);
+    assert_eq!(transaction.cancellers, vec![test_wallet.second_owner.pubkey()]);
+}
+
+#[tokio::test]
+async fn approved_proposal_needs_a_cancellation_quorum() {
+    let mut test_wallet = TestWallet::start().await;
+    let payer = test_wallet.context.payer.pubkey();
+
+    propose_send(&mut test_wallet).await;
+    test_wallet.confirm().await;
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Approved);
+
+    // Once approved the proposer's cancel is only one vote of the threshold.
+    cancel_as_proposer(&mut test_wallet).await.unwrap();
+    let transaction = test_wallet.state().await.transaction;
+    assert_eq!(transaction.status, TransactionStatus::Approved);
+    assert_eq!(transaction.cancellers, vec![payer]);
+
+    test_wallet.next_slot().await;
+    assert_wallet_error(cancel_as_proposer(&mut test_wallet).await, INVALID_INSTRUCTION);
+
+    cancel_as_second_owner(&mut test_wallet).await;
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Cancelled);
+}
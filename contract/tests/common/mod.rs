@@ -0,0 +1,323 @@
+#![allow(dead_code)]
+
+use borsh::BorshSerialize;
+use fresh_guacamole::{
+    entrypoint::process_instruction,
+    instruction::{
+        CreateTransactionPayload,
+        CreateWalletPayload,
+        TransactionReferencePayload
+    },
+    state::{
+        MultiSigWalletState,
+        OwnerRegistryState
+    }
+};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{
+        AccountMeta,
+        Instruction,
+        InstructionError
+    },
+    clock::Clock,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program
+};
+use solana_program_test::{
+    processor,
+    ProgramTest,
+    ProgramTestContext
+};
+use solana_sdk::{
+    signature::{
+        Keypair,
+        Signer
+    },
+    transaction::{
+        Transaction,
+        TransactionError
+    },
+    transport
+};
+
+pub fn wallet_instruction<T: BorshSerialize>(
+    program_id: &Pubkey,
+    tag: u8,
+    payload: T,
+    accounts: Vec<AccountMeta>
+) -> Instruction {
+    let mut data = vec![tag];
+    data.extend(payload.try_to_vec().unwrap());
+
+    Instruction::new_with_bytes(*program_id, &data, accounts)
+}
+
+pub async fn try_process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair]
+) -> transport::Result<()> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    transaction.sign(&all_signers, context.last_blockhash);
+
+    context.banks_client.process_transaction(transaction).await
+}
+
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair]
+) {
+    try_process(context, instruction, signers).await.unwrap();
+}
+
+pub async fn wallet_state(
+    context: &mut ProgramTestContext,
+    wallet: &Pubkey
+) -> MultiSigWalletState {
+    let account = context.banks_client.get_account(*wallet).await.unwrap().unwrap();
+
+    try_from_slice_unchecked::<MultiSigWalletState>(&account.data).unwrap()
+}
+
+pub fn assert_wallet_error(result: transport::Result<()>, code: u32) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(code))
+    );
+}
+
+pub fn wallet_address(program_id: &Pubkey, base: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.as_ref()], program_id).0
+}
+
+pub fn registry_address(program_id: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"registry".as_ref(), owner.as_ref()], program_id).0
+}
+
+/// Writable registry `PDA`s of `owners`, needed by every instruction that
+/// changes who owns the wallet.
+pub fn registry_accounts(program_id: &Pubkey, owners: &[Pubkey]) -> Vec<AccountMeta> {
+    owners.iter()
+        .map(|owner| AccountMeta::new(registry_address(program_id, owner), false))
+        .collect()
+}
+
+/// A 2-of-2 wallet owned by the context payer and `second_owner`, guarded by
+/// `guardian` and with `VAULT_FUNDS` lamports in vault 0. `second_owner` holds
+/// `OWNER_FUNDS` to pay for the receipts of the proposals it closes.
+pub struct TestWallet {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    pub base: Keypair,
+    pub wallet: Pubkey,
+    pub second_owner: Keypair,
+    pub guardian: Keypair
+}
+
+pub const VAULT_FUNDS: u64 = 10_000_000_000;
+pub const OWNER_FUNDS: u64 = 1_000_000_000;
+pub const INACTIVITY_PERIOD: i64 = 86_400;
+
+impl TestWallet {
+    pub async fn start() -> Self {
+        let program_id = Pubkey::new_unique();
+        let base = Keypair::new();
+        let wallet = wallet_address(&program_id, &base.pubkey());
+        let second_owner = Keypair::new();
+        let guardian = Keypair::new();
+
+        let mut context = ProgramTest::new("fresh_guacamole", program_id, processor!(process_instruction)).start_with_context().await;
+        let payer = context.payer.pubkey();
+        let owners = vec![payer, second_owner.pubkey()];
+
+        let mut create_wallet_accounts = vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(base.pubkey(), true),
+            AccountMeta::new(wallet, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false)
+        ];
+        create_wallet_accounts.extend(registry_accounts(&program_id, &owners));
+
+        process(&mut context, wallet_instruction(&program_id, 0, CreateWalletPayload {
+            owners,
+            threshold: 2,
+            guardians: vec![guardian.pubkey()],
+            guardian_threshold: 1,
+            inactivity_period: INACTIVITY_PERIOD,
+            ..Default::default()
+        }, create_wallet_accounts), &[&base]).await;
+
+        let mut test_wallet = TestWallet { context, program_id, base, wallet, second_owner, guardian };
+        let vault = test_wallet.vault(0);
+        process(&mut test_wallet.context, system_instruction::transfer(&payer, &vault, VAULT_FUNDS), &[]).await;
+
+        let second_owner = test_wallet.second_owner.pubkey();
+        test_wallet.fund(&second_owner).await;
+
+        test_wallet
+    }
+
+    pub fn vault(&self, index: u8) -> Pubkey {
+        Pubkey::find_program_address(&[b"vault".as_ref(), self.wallet.as_ref(), &[index]], &self.program_id).0
+    }
+
+    pub fn receipt(&self, transaction_id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[b"receipt".as_ref(), self.wallet.as_ref(), &transaction_id.to_le_bytes()], &self.program_id).0
+    }
+
+    /// PDA derived from `seed`, the wallet and a proposal id, like the
+    /// accounts created by executed proposals.
+    pub fn derived_address(&self, seed: &[u8], transaction_id: u64) -> Pubkey {
+        Pubkey::find_program_address(&[seed, self.wallet.as_ref(), &transaction_id.to_le_bytes()], &self.program_id).0
+    }
+
+    pub async fn state(&mut self) -> MultiSigWalletState {
+        wallet_state(&mut self.context, &self.wallet).await
+    }
+
+    /// The id the next proposal will get.
+    pub async fn next_transaction_id(&mut self) -> u64 {
+        self.state().await.transaction_count + 1
+    }
+
+    pub async fn unix_timestamp(&mut self) -> i64 {
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+    }
+
+    /// Sends `OWNER_FUNDS` from the context payer to `address`.
+    pub async fn fund(&mut self, address: &Pubkey) {
+        let payer = self.context.payer.pubkey();
+        process(&mut self.context, system_instruction::transfer(&payer, address, OWNER_FUNDS), &[]).await;
+    }
+
+    /// Wallets listed in the registry of `owner`, empty when it has none.
+    pub async fn registered_wallets(&mut self, owner: &Pubkey) -> Vec<Pubkey> {
+        let registry = registry_address(&self.program_id, owner);
+
+        match self.context.banks_client.get_account(registry).await.unwrap() {
+            Some(account) => try_from_slice_unchecked::<OwnerRegistryState>(&account.data).unwrap().wallets,
+            None => Vec::new()
+        }
+    }
+
+    pub async fn balance(&mut self, address: Pubkey) -> u64 {
+        self.context.banks_client.get_balance(address).await.unwrap()
+    }
+
+    /// Moves the bank forward so identical instructions get a fresh blockhash.
+    pub async fn next_slot(&mut self) {
+        let slot = self.context.banks_client.get_root_slot().await.unwrap();
+        self.context.warp_to_slot(slot + 2).unwrap();
+        self.context.last_blockhash = self.context.banks_client.get_recent_blockhash().await.unwrap();
+    }
+
+    /// Accounts shared by every wallet instruction, signed by `signer`.
+    pub fn wallet_accounts(&self, signer: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new_readonly(self.base.pubkey(), false),
+            AccountMeta::new(self.wallet, false)
+        ]
+    }
+
+    /// Proposes a transaction as the context payer, `propose_accounts` follow
+    /// the destination account.
+    pub async fn propose(
+        &mut self,
+        payload: CreateTransactionPayload,
+        to: Pubkey,
+        propose_accounts: Vec<AccountMeta>
+    ) {
+        let payer = self.context.payer.pubkey();
+        let mut accounts = self.wallet_accounts(&payer);
+        accounts.push(AccountMeta::new_readonly(to, false));
+        accounts.extend(propose_accounts);
+
+        process(&mut self.context, wallet_instruction(&self.program_id, 1, payload, accounts), &[]).await;
+    }
+
+    /// Receipt of the current transaction and the system program, needed by
+    /// every instruction that can close it.
+    pub async fn closing_accounts(&mut self) -> Vec<AccountMeta> {
+        let transaction_id = self.state().await.transaction.id;
+
+        vec![
+            AccountMeta::new(self.receipt(transaction_id), false),
+            AccountMeta::new_readonly(system_program::id(), false)
+        ]
+    }
+
+    /// Wallet accounts signed by `signer` followed by the closing accounts,
+    /// for rejecting and cancelling.
+    pub async fn closing_wallet_accounts(&mut self, signer: &Pubkey) -> Vec<AccountMeta> {
+        let mut accounts = self.wallet_accounts(signer);
+        accounts.extend(self.closing_accounts().await);
+
+        accounts
+    }
+
+    /// Id and content hash of the current transaction, as reviewed by the
+    /// owners confirming or executing it.
+    pub async fn reference(&mut self) -> TransactionReferencePayload {
+        let transaction = self.state().await.transaction;
+
+        TransactionReferencePayload {
+            transaction_id: transaction.id,
+            transaction_hash: transaction.hash
+        }
+    }
+
+    pub async fn confirm(&mut self) {
+        let reference = self.reference().await;
+        let accounts = self.wallet_accounts(&self.second_owner.pubkey());
+        let instruction = wallet_instruction(&self.program_id, 2, reference, accounts);
+
+        process(&mut self.context, instruction, &[&self.second_owner]).await;
+    }
+
+    /// Executes the approved transaction as the context payer,
+    /// `execute_accounts` follow the receipt and system program.
+    pub async fn try_execute(
+        &mut self,
+        to: Pubkey,
+        execute_accounts: Vec<AccountMeta>
+    ) -> transport::Result<()> {
+        let payer = self.context.payer.pubkey();
+        let mut accounts = self.wallet_accounts(&payer);
+        accounts.push(AccountMeta::new(to, false));
+        accounts.extend(self.closing_accounts().await);
+        accounts.extend(execute_accounts);
+
+        let reference = self.reference().await;
+        try_process(&mut self.context, wallet_instruction(&self.program_id, 4, reference, accounts), &[]).await
+    }
+
+    /// Proposes, confirms with the second owner and executes a transaction.
+    pub async fn propose_and_execute(
+        &mut self,
+        payload: CreateTransactionPayload,
+        to: Pubkey,
+        propose_accounts: Vec<AccountMeta>,
+        execute_accounts: Vec<AccountMeta>
+    ) {
+        self.propose(payload, to, propose_accounts).await;
+        self.confirm().await;
+        self.try_execute(to, execute_accounts).await.unwrap();
+    }
+
+    /// Send proposal accounts, for `propose` and `try_execute` respectively.
+    pub fn send_accounts(&self, vault_index: u8) -> (Vec<AccountMeta>, Vec<AccountMeta>) {
+        let vault = self.vault(vault_index);
+
+        (
+            vec![AccountMeta::new_readonly(vault, false)],
+            vec![AccountMeta::new(vault, false)]
+        )
+    }
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::{
+        TransactionStatus,
+        TransactionVariant
+    }
+};
+use solana_program::{
+    instruction::AccountMeta,
+    pubkey::Pubkey
+};
+use solana_sdk::{
+    signature::{
+        Keypair,
+        Signer
+    },
+    transport
+};
+
+// MultiSigWalletError::InvalidOwner
+const INVALID_OWNER: u32 = 4;
+
+const AMOUNT: u64 = 1_000_000;
+
+async fn execute_as(
+    test_wallet: &mut TestWallet,
+    keeper: &Keypair,
+    to: Pubkey,
+    execute_accounts: Vec<AccountMeta>
+) -> transport::Result<()> {
+    let mut accounts = test_wallet.wallet_accounts(&keeper.pubkey());
+    accounts.push(AccountMeta::new(to, false));
+    accounts.extend(test_wallet.closing_accounts().await);
+    accounts.extend(execute_accounts);
+
+    let reference = test_wallet.reference().await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 4, reference, accounts);
+    common::try_process(&mut test_wallet.context, instruction, &[keeper]).await
+}
+
+async fn propose_approved_send(test_wallet: &mut TestWallet, to: Pubkey) {
+    let (propose_accounts, _) = test_wallet.send_accounts(0);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount: AMOUNT,
+        ..Default::default()
+    }, to, propose_accounts).await;
+    test_wallet.confirm().await;
+}
+
+#[tokio::test]
+async fn non_owner_executes_approved_proposal() {
+    let mut test_wallet = TestWallet::start().await;
+    let keeper = Keypair::new();
+    let to = Pubkey::new_unique();
+
+    // The executor pays for the receipt of the proposal.
+    test_wallet.fund(&keeper.pubkey()).await;
+    propose_approved_send(&mut test_wallet, to).await;
+
+    let (_, execute_accounts) = test_wallet.send_accounts(0);
+    execute_as(&mut test_wallet, &keeper, to, execute_accounts).await.unwrap();
+
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Executed);
+    assert_eq!(test_wallet.balance(to).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn owners_only_execution_rejects_non_owner() {
+    let mut test_wallet = TestWallet::start().await;
+    let keeper = Keypair::new();
+    let to = Pubkey::new_unique();
+    let wallet = test_wallet.wallet;
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::SetExecutionPolicy,
+        owners_only_execution: true,
+        ..Default::default()
+    }, wallet, vec![], vec![]).await;
+    assert!(test_wallet.state().await.owners_only_execution);
+
+    test_wallet.next_slot().await;
+    propose_approved_send(&mut test_wallet, to).await;
+
+    let (_, execute_accounts) = test_wallet.send_accounts(0);
+    let result = execute_as(&mut test_wallet, &keeper, to, execute_accounts.clone()).await;
+    assert_wallet_error(result, INVALID_OWNER);
+
+    test_wallet.try_execute(to, execute_accounts).await.unwrap();
+    assert_eq!(test_wallet.balance(to).await, AMOUNT);
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::TestWallet;
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::TransactionVariant
+};
+use solana_program::{
+    instruction::InstructionError,
+    pubkey::Pubkey
+};
+use solana_sdk::transaction::TransactionError;
+
+const AMOUNT: u64 = 1_000_000;
+const DESCRIPTION: &str = "Invoice 2026-10";
+
+async fn propose_described_send(test_wallet: &mut TestWallet, recipient: Pubkey) {
+    let (propose_accounts, _) = test_wallet.send_accounts(0);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount: AMOUNT,
+        description: DESCRIPTION.to_string(),
+        reference: Some([7; 32]),
+        ..Default::default()
+    }, recipient, propose_accounts).await;
+    test_wallet.confirm().await;
+}
+
+#[tokio::test]
+async fn described_proposal_needs_the_memo_program_to_execute() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    propose_described_send(&mut test_wallet, recipient).await;
+
+    let transaction = test_wallet.state().await.transaction;
+    assert_eq!(transaction.description, DESCRIPTION);
+    assert_eq!(transaction.reference, Some([7; 32]));
+
+    let (_, execute_accounts) = test_wallet.send_accounts(0);
+    let result = test_wallet.try_execute(recipient, execute_accounts).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    assert_eq!(test_wallet.balance(recipient).await, 0);
+}
+
+// The memo program only runs as BPF.
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn described_proposal_executes_with_a_memo() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    propose_described_send(&mut test_wallet, recipient).await;
+
+    let (_, mut execute_accounts) = test_wallet.send_accounts(0);
+    execute_accounts.push(solana_program::instruction::AccountMeta::new_readonly(spl_memo::id(), false));
+    test_wallet.try_execute(recipient, execute_accounts).await.unwrap();
+
+    assert_eq!(test_wallet.state().await.transaction.status, fresh_guacamole::state::TransactionStatus::Executed);
+    assert_eq!(test_wallet.balance(recipient).await, AMOUNT);
+}
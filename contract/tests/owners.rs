@@ -0,0 +1,212 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    registry_accounts,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::TransactionVariant
+};
+use solana_program::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    system_program
+};
+use solana_sdk::{
+    signature::Signer,
+    transport
+};
+
+// MultiSigWalletError::InvalidThreshold
+const INVALID_THRESHOLD: u32 = 2;
+// MultiSigWalletError::OwnerAlreadyExists
+const OWNER_ALREADY_EXISTS: u32 = 17;
+// MultiSigWalletError::OwnerNotFound
+const OWNER_NOT_FOUND: u32 = 18;
+// MultiSigWalletError::DuplicateOwner
+const DUPLICATE_OWNER: u32 = 19;
+// MultiSigWalletError::ForbiddenOwner
+const FORBIDDEN_OWNER: u32 = 20;
+
+async fn try_propose(test_wallet: &mut TestWallet, payload: CreateTransactionPayload) -> transport::Result<()> {
+    let payer = test_wallet.context.payer.pubkey();
+    let mut accounts = test_wallet.wallet_accounts(&payer);
+    accounts.push(AccountMeta::new_readonly(test_wallet.wallet, false));
+
+    let instruction = wallet_instruction(&test_wallet.program_id, 1, payload, accounts);
+    common::try_process(&mut test_wallet.context, instruction, &[]).await
+}
+
+async fn change_owner(test_wallet: &mut TestWallet, variant: TransactionVariant, owner: Pubkey, new_owner: Pubkey) {
+    let wallet = test_wallet.wallet;
+    let changed_owners: Vec<Pubkey> = [owner, new_owner].into_iter().filter(|key| *key != Pubkey::default()).collect();
+    let execute_accounts = registry_accounts(&test_wallet.program_id, &changed_owners);
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant,
+        owner,
+        new_owner,
+        ..Default::default()
+    }, wallet, vec![], execute_accounts).await;
+}
+
+#[tokio::test]
+async fn add_remove_and_swap_owners() {
+    let mut test_wallet = TestWallet::start().await;
+    let payer = test_wallet.context.payer.pubkey();
+    let second_owner = test_wallet.second_owner.pubkey();
+    let third_owner = Pubkey::new_unique();
+    let fourth_owner = Pubkey::new_unique();
+
+    let wallet = test_wallet.wallet;
+    assert_eq!(test_wallet.registered_wallets(&payer).await, vec![wallet]);
+    assert_eq!(test_wallet.registered_wallets(&second_owner).await, vec![wallet]);
+
+    change_owner(&mut test_wallet, TransactionVariant::AddOwner, third_owner, Pubkey::default()).await;
+    assert_eq!(test_wallet.state().await.owners, vec![payer, second_owner, third_owner]);
+    assert_eq!(test_wallet.registered_wallets(&third_owner).await, vec![wallet]);
+
+    test_wallet.next_slot().await;
+    change_owner(&mut test_wallet, TransactionVariant::SwapOwner, third_owner, fourth_owner).await;
+    assert_eq!(test_wallet.state().await.owners, vec![payer, second_owner, fourth_owner]);
+    assert_eq!(test_wallet.registered_wallets(&third_owner).await, vec![]);
+    assert_eq!(test_wallet.registered_wallets(&fourth_owner).await, vec![wallet]);
+
+    test_wallet.next_slot().await;
+    change_owner(&mut test_wallet, TransactionVariant::RemoveOwner, fourth_owner, Pubkey::default()).await;
+    assert_eq!(test_wallet.state().await.owners, vec![payer, second_owner]);
+    assert_eq!(test_wallet.registered_wallets(&fourth_owner).await, vec![]);
+}
+
+#[tokio::test]
+async fn owner_changes_check_membership() {
+    let mut test_wallet = TestWallet::start().await;
+    let second_owner = test_wallet.second_owner.pubkey();
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::AddOwner,
+        owner: second_owner,
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, OWNER_ALREADY_EXISTS);
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::SwapOwner,
+        owner: Pubkey::new_unique(),
+        new_owner: Pubkey::new_unique(),
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, OWNER_NOT_FOUND);
+}
+
+#[tokio::test]
+async fn remove_owner_keeps_the_veto_threshold_reachable() {
+    let mut test_wallet = TestWallet::start().await;
+    let wallet = test_wallet.wallet;
+    let third_owner = Pubkey::new_unique();
+
+    change_owner(&mut test_wallet, TransactionVariant::AddOwner, third_owner, Pubkey::default()).await;
+
+    test_wallet.next_slot().await;
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::SetVetoThreshold,
+        veto_threshold: 3,
+        ..Default::default()
+    }, wallet, vec![], vec![]).await;
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::RemoveOwner,
+        owner: third_owner,
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, INVALID_THRESHOLD);
+}
+
+#[tokio::test]
+async fn reconfigure_sets_owners_and_threshold_together() {
+    let mut test_wallet = TestWallet::start().await;
+    let wallet = test_wallet.wallet;
+    let owners = vec![
+        test_wallet.context.payer.pubkey(),
+        test_wallet.second_owner.pubkey(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique()
+    ];
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::Reconfigure,
+        owners: owners.clone(),
+        threshold: 5,
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, INVALID_THRESHOLD);
+
+    let execute_accounts = registry_accounts(&test_wallet.program_id, &owners[2..]);
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::Reconfigure,
+        owners: owners.clone(),
+        threshold: 3,
+        ..Default::default()
+    }, wallet, vec![], execute_accounts).await;
+
+    let state = test_wallet.state().await;
+    assert_eq!(state.owners, owners);
+    assert_eq!(state.threshold, 3);
+}
+
+#[tokio::test]
+async fn reconfigure_keeps_the_veto_threshold_reachable() {
+    let mut test_wallet = TestWallet::start().await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.context.payer.pubkey();
+    let second_owner = test_wallet.second_owner.pubkey();
+
+    change_owner(&mut test_wallet, TransactionVariant::AddOwner, Pubkey::new_unique(), Pubkey::default()).await;
+
+    test_wallet.next_slot().await;
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::SetVetoThreshold,
+        veto_threshold: 3,
+        ..Default::default()
+    }, wallet, vec![], vec![]).await;
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::Reconfigure,
+        owners: vec![payer, second_owner],
+        threshold: 2,
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, INVALID_THRESHOLD);
+}
+
+#[tokio::test]
+async fn owner_sets_reject_duplicate_and_forbidden_keys() {
+    let mut test_wallet = TestWallet::start().await;
+    let payer = test_wallet.context.payer.pubkey();
+    let wallet = test_wallet.wallet;
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::SetOwners,
+        owners: vec![payer, payer],
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, DUPLICATE_OWNER);
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::Reconfigure,
+        owners: vec![payer, wallet],
+        threshold: 2,
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, FORBIDDEN_OWNER);
+
+    let result = try_propose(&mut test_wallet, CreateTransactionPayload {
+        variant: TransactionVariant::AddOwner,
+        owner: system_program::id(),
+        ..Default::default()
+    }).await;
+    assert_wallet_error(result, FORBIDDEN_OWNER);
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::TransactionVariant
+};
+use solana_program::{
+    instruction::Instruction,
+    pubkey::Pubkey
+};
+use solana_sdk::signature::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+
+// MultiSigWalletError::InvalidOwner and MultiSigWalletError::WalletPaused
+const INVALID_OWNER: u32 = 4;
+const WALLET_PAUSED: u32 = 13;
+
+fn pause_instruction(test_wallet: &TestWallet, signer: &Pubkey) -> Instruction {
+    wallet_instruction(&test_wallet.program_id, 11, (), test_wallet.wallet_accounts(signer))
+}
+
+async fn pause_by_guardian(test_wallet: &mut TestWallet) {
+    let guardian = test_wallet.guardian.pubkey();
+    let instruction = pause_instruction(test_wallet, &guardian);
+    common::process(&mut test_wallet.context, instruction, &[&test_wallet.guardian]).await;
+}
+
+async fn unpause(test_wallet: &mut TestWallet) {
+    let wallet = test_wallet.wallet;
+
+    test_wallet.next_slot().await;
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::Unpause,
+        ..Default::default()
+    }, wallet, vec![], vec![]).await;
+}
+
+#[tokio::test]
+async fn send_waits_for_unpause() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    pause_by_guardian(&mut test_wallet).await;
+    assert!(test_wallet.state().await.is_paused);
+
+    let (propose_accounts, execute_accounts) = test_wallet.send_accounts(0);
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount: AMOUNT,
+        ..Default::default()
+    }, recipient, propose_accounts).await;
+    test_wallet.confirm().await;
+
+    let result = test_wallet.try_execute(recipient, execute_accounts.clone()).await;
+    assert_wallet_error(result, WALLET_PAUSED);
+    assert_eq!(test_wallet.balance(recipient).await, 0);
+
+    // The approved send has to be cancelled by both owners before the
+    // unpause can be proposed.
+    let payer = test_wallet.context.payer.pubkey();
+    let accounts = test_wallet.closing_wallet_accounts(&payer).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 5, (), accounts);
+    common::process(&mut test_wallet.context, instruction, &[]).await;
+
+    let second_owner = test_wallet.second_owner.pubkey();
+    let accounts = test_wallet.closing_wallet_accounts(&second_owner).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 5, (), accounts);
+    common::process(&mut test_wallet.context, instruction, &[&test_wallet.second_owner]).await;
+
+    unpause(&mut test_wallet).await;
+    assert!(!test_wallet.state().await.is_paused);
+
+    test_wallet.next_slot().await;
+    let (propose_accounts, execute_accounts) = test_wallet.send_accounts(0);
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount: AMOUNT,
+        ..Default::default()
+    }, recipient, propose_accounts, execute_accounts).await;
+    assert_eq!(test_wallet.balance(recipient).await, AMOUNT);
+}
+
+#[tokio::test]
+async fn rogue_guardian_is_removed_while_paused() {
+    let mut test_wallet = TestWallet::start().await;
+    let wallet = test_wallet.wallet;
+
+    pause_by_guardian(&mut test_wallet).await;
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::SetGuardians,
+        ..Default::default()
+    }, wallet, vec![], vec![]).await;
+
+    let state = test_wallet.state().await;
+    assert!(state.is_paused);
+    assert!(state.guardians.is_empty());
+
+    unpause(&mut test_wallet).await;
+
+    // The removed guardian can no longer pause the wallet again.
+    test_wallet.next_slot().await;
+    let guardian = test_wallet.guardian.pubkey();
+    let instruction = pause_instruction(&test_wallet, &guardian);
+    let result = common::try_process(&mut test_wallet.context, instruction, &[&test_wallet.guardian]).await;
+    assert_wallet_error(result, INVALID_OWNER);
+    assert!(!test_wallet.state().await.is_paused);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::{
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::{
+        TransactionReceiptState,
+        TransactionStatus,
+        TransactionVariant
+    }
+};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    pubkey::Pubkey,
+    system_instruction
+};
+use solana_sdk::signature::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+
+async fn propose_send(test_wallet: &mut TestWallet, recipient: Pubkey) {
+    let (propose_accounts, _) = test_wallet.send_accounts(0);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount: AMOUNT,
+        ..Default::default()
+    }, recipient, propose_accounts).await;
+}
+
+async fn receipt_state(test_wallet: &mut TestWallet, transaction_id: u64) -> TransactionReceiptState {
+    let receipt = test_wallet.receipt(transaction_id);
+    let account = test_wallet.context.banks_client.get_account(receipt).await.unwrap().unwrap();
+    assert_eq!(account.owner, test_wallet.program_id);
+
+    try_from_slice_unchecked::<TransactionReceiptState>(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn executed_proposal_leaves_a_receipt() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+    let payer = test_wallet.context.payer.pubkey();
+
+    propose_send(&mut test_wallet, recipient).await;
+    test_wallet.confirm().await;
+    let transaction_id = test_wallet.state().await.transaction.id;
+
+    let (_, execute_accounts) = test_wallet.send_accounts(0);
+    test_wallet.try_execute(recipient, execute_accounts).await.unwrap();
+
+    let receipt = receipt_state(&mut test_wallet, transaction_id).await;
+    assert_eq!(receipt.wallet, test_wallet.wallet);
+    assert_eq!(receipt.executor, payer);
+    assert_eq!(receipt.transaction.id, transaction_id);
+    assert_eq!(receipt.transaction.status, TransactionStatus::Executed);
+    assert_eq!(receipt.transaction.to_address, recipient);
+}
+
+#[tokio::test]
+async fn prefunded_receipt_address_does_not_block_closing() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+    let payer = test_wallet.context.payer.pubkey();
+
+    propose_send(&mut test_wallet, recipient).await;
+    let transaction_id = test_wallet.state().await.transaction.id;
+    let receipt = test_wallet.receipt(transaction_id);
+
+    // Anyone can send lamports to the receipt address before the proposal ends.
+    common::process(&mut test_wallet.context, system_instruction::transfer(&payer, &receipt, 1), &[]).await;
+
+    let accounts = test_wallet.closing_wallet_accounts(&payer).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 5, (), accounts);
+    common::process(&mut test_wallet.context, instruction, &[]).await;
+
+    let receipt = receipt_state(&mut test_wallet, transaction_id).await;
+    assert!(receipt.is_initialized);
+    assert_eq!(receipt.transaction.status, TransactionStatus::Cancelled);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::{
+        BatchActionState,
+        ScheduledPaymentState,
+        TransactionVariant
+    }
+};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{
+        AccountMeta,
+        Instruction
+    },
+    pubkey::Pubkey,
+    system_program
+};
+use solana_sdk::signature::Signer;
+
+const AMOUNT: u64 = 1_000_000;
+const INTERVAL: i64 = 10;
+
+// MultiSigWalletError::InvalidPDA, MultiSigWalletError::UnexpectedInstruction
+// and MultiSigWalletError::DestinationNotAllowed
+const INVALID_PDA: u32 = 1;
+const UNEXPECTED_INSTRUCTION: u32 = 6;
+const DESTINATION_NOT_ALLOWED: u32 = 15;
+
+async fn create_schedule(test_wallet: &mut TestWallet, recipient: Pubkey, start_at: i64, end_at: i64) -> Pubkey {
+    let transaction_id = test_wallet.next_transaction_id().await;
+    let schedule = test_wallet.derived_address(b"schedule", transaction_id);
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::CreateSchedule,
+        amount: AMOUNT,
+        period: INTERVAL,
+        start_at,
+        end_at,
+        ..Default::default()
+    }, recipient, vec![], vec![AccountMeta::new(schedule, false)]).await;
+
+    schedule
+}
+
+fn claim_instruction(test_wallet: &TestWallet, schedule: Pubkey, recipient: Pubkey) -> Instruction {
+    wallet_instruction(&test_wallet.program_id, 14, (), vec![
+        AccountMeta::new_readonly(test_wallet.base.pubkey(), false),
+        AccountMeta::new(test_wallet.wallet, false),
+        AccountMeta::new(schedule, false),
+        AccountMeta::new(test_wallet.vault(0), false),
+        AccountMeta::new(recipient, false),
+        AccountMeta::new_readonly(system_program::id(), false)
+    ])
+}
+
+async fn schedule_state(test_wallet: &mut TestWallet, schedule: Pubkey) -> ScheduledPaymentState {
+    let account = test_wallet.context.banks_client.get_account(schedule).await.unwrap().unwrap();
+
+    try_from_slice_unchecked::<ScheduledPaymentState>(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn claim_catches_up_missed_payments() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    let now = test_wallet.unix_timestamp().await;
+    let start_at = now - 5 * INTERVAL;
+    let schedule = create_schedule(&mut test_wallet, recipient, start_at, now + 100 * INTERVAL).await;
+
+    let vault = test_wallet.vault(0);
+    let vault_before = test_wallet.balance(vault).await;
+    let instruction = claim_instruction(&test_wallet, schedule, recipient);
+    common::process(&mut test_wallet.context, instruction, &[]).await;
+
+    let claimed_at = test_wallet.unix_timestamp().await;
+    let payments = ((claimed_at - start_at) / INTERVAL + 1) as u64;
+    let schedule_data = schedule_state(&mut test_wallet, schedule).await;
+
+    assert!(payments >= 6);
+    assert_eq!(schedule_data.payments_made, payments);
+    assert_eq!(schedule_data.next_payment_at, start_at + payments as i64 * INTERVAL);
+    assert_eq!(test_wallet.balance(recipient).await, payments * AMOUNT);
+    assert_eq!(test_wallet.balance(vault).await, vault_before - payments * AMOUNT);
+}
+
+#[tokio::test]
+async fn claim_stops_at_end_date() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    let now = test_wallet.unix_timestamp().await;
+    let start_at = now - 3 * INTERVAL;
+    let end_at = now + 1;
+    let schedule = create_schedule(&mut test_wallet, recipient, start_at, end_at).await;
+
+    let instruction = claim_instruction(&test_wallet, schedule, recipient);
+    common::process(&mut test_wallet.context, instruction, &[]).await;
+
+    let claimed_at = test_wallet.unix_timestamp().await;
+    let payments = ((claimed_at.min(end_at) - start_at) / INTERVAL + 1) as u64;
+    assert_eq!(schedule_state(&mut test_wallet, schedule).await.payments_made, payments);
+    assert_eq!(test_wallet.balance(recipient).await, payments * AMOUNT);
+
+    // Every payment up to the end date has been made, so nothing is ever due again.
+    test_wallet.next_slot().await;
+    let instruction = claim_instruction(&test_wallet, schedule, recipient);
+    assert_wallet_error(common::try_process(&mut test_wallet.context, instruction, &[]).await, UNEXPECTED_INSTRUCTION);
+    assert_eq!(test_wallet.balance(recipient).await, payments * AMOUNT);
+}
+
+#[tokio::test]
+async fn cancelled_schedule_cannot_be_claimed() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    let now = test_wallet.unix_timestamp().await;
+    let schedule = create_schedule(&mut test_wallet, recipient, now - INTERVAL, now + 100 * INTERVAL).await;
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::CancelSchedule,
+        accounts: vec![schedule],
+        ..Default::default()
+    }, test_wallet.wallet, vec![], vec![AccountMeta::new(schedule, false)]).await;
+    assert!(schedule_state(&mut test_wallet, schedule).await.is_cancelled);
+
+    let instruction = claim_instruction(&test_wallet, schedule, recipient);
+    assert_wallet_error(common::try_process(&mut test_wallet.context, instruction, &[]).await, UNEXPECTED_INSTRUCTION);
+    assert_eq!(test_wallet.balance(recipient).await, 0);
+}
+
+#[tokio::test]
+async fn claim_checks_the_current_allowlist() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    let now = test_wallet.unix_timestamp().await;
+    let schedule = create_schedule(&mut test_wallet, recipient, now - INTERVAL, now + 100 * INTERVAL).await;
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::SetAllowlist,
+        allowlist_enabled: true,
+        ..Default::default()
+    }, test_wallet.wallet, vec![], vec![]).await;
+
+    let instruction = claim_instruction(&test_wallet, schedule, recipient);
+    assert_wallet_error(common::try_process(&mut test_wallet.context, instruction, &[]).await, DESTINATION_NOT_ALLOWED);
+    assert_eq!(test_wallet.balance(recipient).await, 0);
+}
+
+#[tokio::test]
+async fn claim_rejects_account_that_is_not_the_schedule_pda() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    let transaction_id = test_wallet.next_transaction_id().await;
+    let batch = test_wallet.derived_address(b"batch", transaction_id);
+
+    // A batch account shares the schedule's leading fields.
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Batch,
+        actions: vec![BatchActionState {
+            to_address: recipient,
+            amount: AMOUNT,
+            ..Default::default()
+        }],
+        ..Default::default()
+    }, Pubkey::new_unique(), vec![
+        AccountMeta::new(batch, false),
+        AccountMeta::new_readonly(system_program::id(), false)
+    ]).await;
+
+    let instruction = claim_instruction(&test_wallet, batch, recipient);
+    assert_wallet_error(common::try_process(&mut test_wallet.context, instruction, &[]).await, INVALID_PDA);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::TestWallet;
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::TransactionVariant
+};
+use solana_program::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    stake::{
+        self,
+        state::StakeState
+    },
+    system_instruction,
+    sysvar
+};
+use solana_sdk::signature::{
+    Keypair,
+    Signer
+};
+use solana_vote_program::{
+    vote_instruction,
+    vote_state::VoteInit
+};
+
+const STAKE_AMOUNT: u64 = 2_000_000_000;
+
+async fn create_vote_account(test_wallet: &mut TestWallet) -> Pubkey {
+    let vote = Keypair::new();
+    let payer = test_wallet.context.payer.pubkey();
+
+    let instructions = vote_instruction::create_account(&payer, &vote.pubkey(), &VoteInit {
+        node_pubkey: payer,
+        authorized_voter: payer,
+        authorized_withdrawer: payer,
+        commission: 0
+    }, 1_000_000_000);
+
+    // Only creating the vote account needs its signature, the node is the payer.
+    let [create, initialize]: [_; 2] = instructions.try_into().unwrap();
+    common::process(&mut test_wallet.context, create, &[&vote]).await;
+    common::process(&mut test_wallet.context, initialize, &[]).await;
+
+    vote.pubkey()
+}
+
+#[tokio::test]
+async fn prefunded_stake_address_does_not_block_delegation() {
+    let mut test_wallet = TestWallet::start().await;
+    let vote = create_vote_account(&mut test_wallet).await;
+    let vault = test_wallet.vault(0);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::DelegateStake,
+        amount: STAKE_AMOUNT,
+        accounts: vec![vote],
+        ..Default::default()
+    }, Pubkey::default(), vec![]).await;
+
+    let transaction_id = test_wallet.state().await.transaction.id;
+    let (stake_account, _) = Pubkey::find_program_address(&[b"stake".as_ref(), test_wallet.wallet.as_ref(), &transaction_id.to_le_bytes()], &test_wallet.program_id);
+
+    // Anyone can send lamports to the stake address before the delegation.
+    let payer = test_wallet.context.payer.pubkey();
+    common::process(&mut test_wallet.context, system_instruction::transfer(&payer, &stake_account, 1), &[]).await;
+
+    let vault_balance = test_wallet.balance(vault).await;
+
+    test_wallet.confirm().await;
+    test_wallet.try_execute(Pubkey::default(), vec![
+        AccountMeta::new(vault, false),
+        AccountMeta::new(stake_account, false),
+        AccountMeta::new_readonly(vote, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::stake_history::id(), false),
+        AccountMeta::new_readonly(stake::config::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(stake::program::id(), false)
+    ]).await.unwrap();
+
+    let account = test_wallet.context.banks_client.get_account(stake_account).await.unwrap().unwrap();
+    assert_eq!(account.owner, stake::program::id());
+    assert_eq!(account.lamports, STAKE_AMOUNT);
+    assert_eq!(test_wallet.balance(vault).await, vault_balance - (STAKE_AMOUNT - 1));
+
+    let stake_state: StakeState = bincode::deserialize(&account.data).unwrap();
+    let delegation = stake_state.delegation().unwrap();
+    assert_eq!(delegation.voter_pubkey, vote);
+
+    let authorized = stake_state.authorized().unwrap();
+    assert_eq!(authorized.staker, test_wallet.wallet);
+    assert_eq!(authorized.withdrawer, test_wallet.wallet);
+}
@@ -0,0 +1,159 @@
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::{
+    process,
+    registry_accounts,
+    wallet_instruction,
+    wallet_state
+};
+use fresh_guacamole::{
+    entrypoint::process_instruction,
+    instruction::{
+        CreateTransactionPayload,
+        CreateWalletPayload,
+        TransactionReferencePayload
+    },
+    state::TransactionVariant
+};
+use solana_program::{
+    bpf_loader_upgradeable::{
+        self,
+        UpgradeableLoaderState
+    },
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    system_program,
+    sysvar
+};
+use solana_program_test::{
+    find_file,
+    processor,
+    read_file,
+    ProgramTest
+};
+use solana_sdk::{
+    account::Account,
+    signature::{
+        Keypair,
+        Signer
+    }
+};
+
+// Requires the program to be built first (`cargo test-bpf`), its ELF is reused
+// as both the deployed dummy program and the upgrade buffer.
+#[tokio::test]
+async fn upgrade_program_with_two_of_three() {
+    let program_id = Pubkey::new_unique();
+    let base = Keypair::new();
+    let (wallet, _) = Pubkey::find_program_address(&[b"MultiSigWallet".as_ref(), base.pubkey().as_ref()], &program_id);
+
+    let elf = read_file(find_file("fresh_guacamole.so").expect("fresh_guacamole.so not found, run cargo test-bpf"));
+    let dummy_program = Pubkey::new_unique();
+    let (programdata, _) = Pubkey::find_program_address(&[dummy_program.as_ref()], &bpf_loader_upgradeable::id());
+    let buffer = Pubkey::new_unique();
+    let spill = Pubkey::new_unique();
+
+    let mut program_test = ProgramTest::new("fresh_guacamole", program_id, processor!(process_instruction));
+
+    program_test.add_account(dummy_program, Account {
+        lamports: 1_000_000_000,
+        data: bincode::serialize(&UpgradeableLoaderState::Program { programdata_address: programdata }).unwrap(),
+        owner: bpf_loader_upgradeable::id(),
+        executable: true,
+        rent_epoch: 0
+    });
+
+    let mut programdata_data = bincode::serialize(&UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address: Some(wallet) }).unwrap();
+    programdata_data.resize(UpgradeableLoaderState::programdata_data_offset().unwrap(), 0);
+    programdata_data.extend_from_slice(&elf);
+    program_test.add_account(programdata, Account {
+        lamports: 1_000_000_000,
+        data: programdata_data,
+        owner: bpf_loader_upgradeable::id(),
+        executable: false,
+        rent_epoch: 0
+    });
+
+    let mut buffer_data = bincode::serialize(&UpgradeableLoaderState::Buffer { authority_address: Some(wallet) }).unwrap();
+    buffer_data.resize(UpgradeableLoaderState::buffer_data_offset().unwrap(), 0);
+    buffer_data.extend_from_slice(&elf);
+    program_test.add_account(buffer, Account {
+        lamports: 1_000_000_000,
+        data: buffer_data,
+        owner: bpf_loader_upgradeable::id(),
+        executable: false,
+        rent_epoch: 0
+    });
+
+    let mut context = program_test.start_with_context().await;
+    context.warp_to_slot(2).unwrap();
+
+    let second_owner = Keypair::new();
+    let third_owner = Keypair::new();
+    let owners = vec![context.payer.pubkey(), second_owner.pubkey(), third_owner.pubkey()];
+    let payer = context.payer.pubkey();
+
+    let mut create_wallet_accounts = vec![
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(base.pubkey(), true),
+        AccountMeta::new(wallet, false),
+        AccountMeta::new_readonly(dummy_program, false),
+        AccountMeta::new_readonly(system_program::id(), false)
+    ];
+    create_wallet_accounts.extend(registry_accounts(&program_id, &owners));
+
+    process(&mut context, wallet_instruction(&program_id, 0, CreateWalletPayload {
+        owners,
+        threshold: 2,
+        ..Default::default()
+    }, create_wallet_accounts), &[&base]).await;
+
+    process(&mut context, wallet_instruction(&program_id, 1, CreateTransactionPayload {
+        variant: TransactionVariant::UpgradeProgram,
+        accounts: vec![dummy_program, buffer, spill],
+        ..Default::default()
+    }, vec![
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(base.pubkey(), false),
+        AccountMeta::new(wallet, false),
+        AccountMeta::new_readonly(dummy_program, false)
+    ]), &[]).await;
+
+    let transaction = wallet_state(&mut context, &wallet).await.transaction;
+
+    process(&mut context, wallet_instruction(&program_id, 2, TransactionReferencePayload {
+        transaction_id: transaction.id,
+        transaction_hash: transaction.hash
+    }, vec![
+        AccountMeta::new_readonly(second_owner.pubkey(), true),
+        AccountMeta::new_readonly(base.pubkey(), false),
+        AccountMeta::new(wallet, false)
+    ]), &[&second_owner]).await;
+
+    let (receipt, _) = Pubkey::find_program_address(&[b"receipt".as_ref(), wallet.as_ref(), &transaction.id.to_le_bytes()], &program_id);
+
+    process(&mut context, wallet_instruction(&program_id, 4, TransactionReferencePayload {
+        transaction_id: transaction.id,
+        transaction_hash: transaction.hash
+    }, vec![
+        AccountMeta::new(payer, true),
+        AccountMeta::new_readonly(base.pubkey(), false),
+        AccountMeta::new(wallet, false),
+        AccountMeta::new(dummy_program, false),
+        AccountMeta::new(receipt, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(dummy_program, false),
+        AccountMeta::new(programdata, false),
+        AccountMeta::new(buffer, false),
+        AccountMeta::new(spill, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(bpf_loader_upgradeable::id(), false)
+    ]), &[]).await;
+
+    assert!(context.banks_client.get_account(buffer).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(spill).await.unwrap().unwrap().lamports > 0);
+    assert!(!wallet_state(&mut context, &wallet).await.transaction.status.is_pending());
+}
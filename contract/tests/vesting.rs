@@ -0,0 +1,157 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::{
+        TransactionVariant,
+        VestingState
+    }
+};
+use solana_program::{
+    borsh::try_from_slice_unchecked,
+    instruction::{
+        AccountMeta,
+        Instruction
+    },
+    pubkey::Pubkey
+};
+use solana_sdk::signature::{
+    Keypair,
+    Signer
+};
+
+const TOTAL: u64 = 1_000_000_000;
+
+// MultiSigWalletError::UnexpectedInstruction and MultiSigWalletError::WalletPaused
+const NOTHING_VESTED: u32 = 6;
+const WALLET_PAUSED: u32 = 13;
+
+async fn create_vesting(test_wallet: &mut TestWallet, recipient: Pubkey, start_at: i64, cliff_at: i64, end_at: i64) -> Pubkey {
+    let transaction_id = test_wallet.next_transaction_id().await;
+    let vesting = test_wallet.derived_address(b"vesting", transaction_id);
+    let vault = test_wallet.vault(0);
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::CreateVesting,
+        amount: TOTAL,
+        start_at,
+        cliff_at,
+        end_at,
+        ..Default::default()
+    }, recipient, vec![], vec![AccountMeta::new(vesting, false), AccountMeta::new(vault, false)]).await;
+
+    vesting
+}
+
+fn withdraw_instruction(test_wallet: &TestWallet, vesting: Pubkey, recipient: Pubkey) -> Instruction {
+    wallet_instruction(&test_wallet.program_id, 15, (), vec![
+        AccountMeta::new(recipient, true),
+        AccountMeta::new_readonly(test_wallet.base.pubkey(), false),
+        AccountMeta::new(test_wallet.wallet, false),
+        AccountMeta::new(vesting, false)
+    ])
+}
+
+async fn vesting_state(test_wallet: &mut TestWallet, vesting: Pubkey) -> VestingState {
+    let account = test_wallet.context.banks_client.get_account(vesting).await.unwrap().unwrap();
+
+    try_from_slice_unchecked::<VestingState>(&account.data).unwrap()
+}
+
+fn vested(start_at: i64, end_at: i64, timestamp: i64) -> u64 {
+    (TOTAL as u128 * (timestamp.min(end_at) - start_at) as u128 / (end_at - start_at) as u128) as u64
+}
+
+#[tokio::test]
+async fn withdraw_before_cliff_fails() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Keypair::new();
+
+    let now = test_wallet.unix_timestamp().await;
+    let vesting = create_vesting(&mut test_wallet, recipient.pubkey(), now - 50, now + 1_000, now + 2_000).await;
+
+    let instruction = withdraw_instruction(&test_wallet, vesting, recipient.pubkey());
+    assert_wallet_error(common::try_process(&mut test_wallet.context, instruction, &[&recipient]).await, NOTHING_VESTED);
+    assert_eq!(vesting_state(&mut test_wallet, vesting).await.withdrawn, 0);
+}
+
+#[tokio::test]
+async fn withdraw_pays_out_linearly_vested_amount() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Keypair::new();
+
+    let now = test_wallet.unix_timestamp().await;
+    let (start_at, end_at) = (now - 100, now + 100);
+    let vesting = create_vesting(&mut test_wallet, recipient.pubkey(), start_at, now - 10, end_at).await;
+
+    let instruction = withdraw_instruction(&test_wallet, vesting, recipient.pubkey());
+    common::process(&mut test_wallet.context, instruction, &[&recipient]).await;
+
+    let withdrawn_at = test_wallet.unix_timestamp().await;
+    let expected = vested(start_at, end_at, withdrawn_at);
+
+    assert!(expected >= TOTAL / 2);
+    assert_eq!(vesting_state(&mut test_wallet, vesting).await.withdrawn, expected);
+    assert_eq!(test_wallet.balance(recipient.pubkey()).await, expected);
+}
+
+#[tokio::test]
+async fn cancel_refunds_unvested_amount_to_vault() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Keypair::new();
+    let vault = test_wallet.vault(0);
+
+    let now = test_wallet.unix_timestamp().await;
+    let (start_at, end_at) = (now - 100, now + 100);
+    let vesting = create_vesting(&mut test_wallet, recipient.pubkey(), start_at, now - 10, end_at).await;
+
+    let instruction = withdraw_instruction(&test_wallet, vesting, recipient.pubkey());
+    common::process(&mut test_wallet.context, instruction, &[&recipient]).await;
+    let withdrawn = vesting_state(&mut test_wallet, vesting).await.withdrawn;
+
+    let vault_before = test_wallet.balance(vault).await;
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::CancelVesting,
+        accounts: vec![vesting],
+        ..Default::default()
+    }, vesting, vec![], vec![AccountMeta::new(vesting, false), AccountMeta::new(vault, false)]).await;
+
+    let cancelled_at = test_wallet.unix_timestamp().await;
+    let vested_total = vested(start_at, end_at, cancelled_at);
+    let vesting_data = vesting_state(&mut test_wallet, vesting).await;
+
+    assert!(vesting_data.is_cancelled);
+    assert_eq!(vesting_data.total, vested_total);
+    assert_eq!(test_wallet.balance(vault).await, vault_before + TOTAL - vested_total);
+
+    // Whatever vested before the cancellation stays claimable.
+    if vested_total > withdrawn {
+        test_wallet.next_slot().await;
+        let instruction = withdraw_instruction(&test_wallet, vesting, recipient.pubkey());
+        common::process(&mut test_wallet.context, instruction, &[&recipient]).await;
+    }
+
+    assert_eq!(test_wallet.balance(recipient.pubkey()).await, vested_total);
+}
+
+#[tokio::test]
+async fn withdraw_fails_while_paused() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Keypair::new();
+
+    let now = test_wallet.unix_timestamp().await;
+    let vesting = create_vesting(&mut test_wallet, recipient.pubkey(), now - 100, now - 10, now + 100).await;
+
+    let guardian = test_wallet.guardian.pubkey();
+    let instruction = wallet_instruction(&test_wallet.program_id, 11, (), test_wallet.wallet_accounts(&guardian));
+    common::process(&mut test_wallet.context, instruction, &[&test_wallet.guardian]).await;
+
+    let instruction = withdraw_instruction(&test_wallet, vesting, recipient.pubkey());
+    assert_wallet_error(common::try_process(&mut test_wallet.context, instruction, &[&recipient]).await, WALLET_PAUSED);
+    assert_eq!(test_wallet.balance(recipient.pubkey()).await, 0);
+}
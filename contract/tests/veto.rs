@@ -0,0 +1,134 @@
+mod common;
+
+use common::{
+    assert_wallet_error,
+    registry_accounts,
+    wallet_instruction,
+    TestWallet
+};
+use fresh_guacamole::{
+    instruction::CreateTransactionPayload,
+    state::{
+        TransactionStatus,
+        TransactionVariant
+    }
+};
+use solana_program::{
+    instruction::AccountMeta,
+    pubkey::Pubkey
+};
+use solana_sdk::signature::{
+    Keypair,
+    Signer
+};
+
+const AMOUNT: u64 = 1_000_000;
+
+// MultiSigWalletError::InvalidThreshold
+const INVALID_THRESHOLD: u32 = 2;
+
+async fn reject(test_wallet: &mut TestWallet, owner: &Keypair) {
+    let accounts = test_wallet.closing_wallet_accounts(&owner.pubkey()).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 3, (), accounts);
+    common::process(&mut test_wallet.context, instruction, &[owner]).await;
+}
+
+async fn propose_send(test_wallet: &mut TestWallet, recipient: Pubkey) {
+    let (propose_accounts, _) = test_wallet.send_accounts(0);
+
+    test_wallet.propose(CreateTransactionPayload {
+        variant: TransactionVariant::Send,
+        amount: AMOUNT,
+        ..Default::default()
+    }, recipient, propose_accounts).await;
+}
+
+/// Turns the 2-of-2 test wallet into a 2-of-3 wallet with `veto_threshold`.
+async fn add_third_owner(test_wallet: &mut TestWallet, veto_threshold: u64) -> Keypair {
+    let third_owner = Keypair::new();
+    let wallet = test_wallet.wallet;
+    let owners = vec![test_wallet.context.payer.pubkey(), test_wallet.second_owner.pubkey(), third_owner.pubkey()];
+    let execute_accounts = registry_accounts(&test_wallet.program_id, &[third_owner.pubkey()]);
+
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::SetOwners,
+        owners,
+        ..Default::default()
+    }, wallet, vec![], execute_accounts).await;
+
+    test_wallet.next_slot().await;
+    test_wallet.propose_and_execute(CreateTransactionPayload {
+        variant: TransactionVariant::SetVetoThreshold,
+        veto_threshold,
+        ..Default::default()
+    }, wallet, vec![], vec![]).await;
+
+    test_wallet.fund(&third_owner.pubkey()).await;
+
+    third_owner
+}
+
+#[tokio::test]
+async fn rejection_that_leaves_too_few_owners_closes_the_proposal() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+
+    propose_send(&mut test_wallet, recipient).await;
+
+    let second_owner = test_wallet.second_owner.pubkey();
+    let accounts = test_wallet.closing_wallet_accounts(&second_owner).await;
+    let instruction = wallet_instruction(&test_wallet.program_id, 3, (), accounts);
+    common::process(&mut test_wallet.context, instruction, &[&test_wallet.second_owner]).await;
+
+    assert_eq!(test_wallet.state().await.transaction.status, TransactionStatus::Rejected);
+}
+
+#[tokio::test]
+async fn veto_closes_a_proposal_that_could_still_pass() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+    let third_owner = add_third_owner(&mut test_wallet, 1).await;
+
+    propose_send(&mut test_wallet, recipient).await;
+
+    // Two of three owners could still approve, only the veto closes it.
+    reject(&mut test_wallet, &third_owner).await;
+
+    let state = test_wallet.state().await;
+    assert_eq!(state.veto_threshold, 1);
+    assert_eq!(state.transaction.status, TransactionStatus::Rejected);
+    assert_eq!(test_wallet.balance(recipient).await, 0);
+}
+
+#[tokio::test]
+async fn rejection_below_the_veto_threshold_keeps_the_proposal() {
+    let mut test_wallet = TestWallet::start().await;
+    let recipient = Pubkey::new_unique();
+    let third_owner = add_third_owner(&mut test_wallet, 2).await;
+
+    propose_send(&mut test_wallet, recipient).await;
+    reject(&mut test_wallet, &third_owner).await;
+
+    let transaction = test_wallet.state().await.transaction;
+    assert_eq!(transaction.status, TransactionStatus::Active);
+    assert_eq!(transaction.opponents, vec![third_owner.pubkey()]);
+}
+
+#[tokio::test]
+async fn owners_cannot_shrink_below_the_veto_threshold() {
+    let mut test_wallet = TestWallet::start().await;
+    let wallet = test_wallet.wallet;
+    add_third_owner(&mut test_wallet, 3).await;
+
+    let payer = test_wallet.context.payer.pubkey();
+    let mut accounts = test_wallet.wallet_accounts(&payer);
+    accounts.push(AccountMeta::new_readonly(wallet, false));
+    let instruction = wallet_instruction(&test_wallet.program_id, 1, CreateTransactionPayload {
+        variant: TransactionVariant::SetOwners,
+        owners: vec![payer, test_wallet.second_owner.pubkey()],
+        ..Default::default()
+    }, accounts);
+
+    let result = common::try_process(&mut test_wallet.context, instruction, &[]).await;
+    assert_wallet_error(result, INVALID_THRESHOLD);
+}